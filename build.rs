@@ -1,7 +1,8 @@
-extern crate prost_build;
 use std::io::Result;
 
 fn main() -> Result<()> {
-    prost_build::compile_protos(&["proto/prices.proto"], &["proto"])?;
+    tonic_build::configure()
+        .build_client(false)
+        .compile(&["proto/prices.proto", "proto/service.proto"], &["proto"])?;
     Ok(())
 }
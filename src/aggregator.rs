@@ -1,12 +1,20 @@
 use crate::collector::MarketData;
-use crate::Error;
 use bigdecimal::BigDecimal;
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
+// aggregated ticker price with providers it's made of
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatedPrice {
+    pub data: MarketData,
+    pub sources: Vec<String>,
+}
+
 pub struct PriceAggregator {
     ttl: Duration,
+    provider_ttls: HashMap<String, Duration>,
+    pinning_providers: HashSet<String>,
     prices_map: HashMap<String, Vec<MarketData>>,
 }
 
@@ -14,10 +22,22 @@ impl PriceAggregator {
     pub fn new(ttl: &Duration) -> Self {
         PriceAggregator {
             prices_map: HashMap::new(),
+            provider_ttls: HashMap::new(),
+            pinning_providers: HashSet::new(),
             ttl: *ttl,
         }
     }
 
+    // override market data ttl for provider
+    pub fn set_provider_ttl(&mut self, provider: &str, ttl: &Duration) {
+        self.provider_ttls.insert(provider.to_string(), *ttl);
+    }
+
+    // fresh market data of pinning provider overrides other sources of the ticker
+    pub fn set_pinning_provider(&mut self, provider: &str) {
+        self.pinning_providers.insert(provider.to_string());
+    }
+
     // consume market data (insert/update)
     pub fn consume(&mut self, market_data: &MarketData) {
         match self.prices_map.get_mut(&market_data.ticker) {
@@ -30,6 +50,9 @@ impl PriceAggregator {
                         price_record.price = market_data.price.clone();
                         price_record.volume = market_data.volume.clone();
                         price_record.timestamp = market_data.timestamp;
+                        price_record.book = market_data.book.clone();
+                        price_record.candle = market_data.candle.clone();
+                        price_record.latency_ms = market_data.latency_ms;
                     }
                     None => prices.push(market_data.clone()),
                 };
@@ -41,61 +64,116 @@ impl PriceAggregator {
         };
     }
 
-    // collect avg result
-    pub fn aggregate(&self) -> Result<Vec<MarketData>, Error> {
-        match self
+    // fresh ticker prices and round prices made of them: all tickers, or none
+    // if some ticker has no fresh market data
+    pub fn aggregate(&self) -> (Vec<AggregatedPrice>, Vec<AggregatedPrice>) {
+        let fresh_prices = self.prices();
+        let round_prices = if fresh_prices.len() == self.prices_map.len() {
+            fresh_prices.clone()
+        } else {
+            vec![]
+        };
+        (fresh_prices, round_prices)
+    }
+
+    // aggregated prices of tickers having fresh market data, sorted by ticker
+    pub fn prices(&self) -> Vec<AggregatedPrice> {
+        let mut prices = self
             .prices_map
             .values()
+            .filter_map(|prices| self.aggregate_prices(prices))
+            .collect::<Vec<_>>();
+        prices.sort_by(|a, b| a.data.ticker.cmp(&b.data.ticker));
+        prices
+    }
+
+    pub fn price(&self, ticker: &str) -> Option<AggregatedPrice> {
+        self.prices_map
+            .get(ticker)
+            .and_then(|prices| self.aggregate_prices(prices))
+    }
+
+    // raw market data of ticker providers, including expired ones
+    pub fn quotes(&self, ticker: &str) -> Option<&[MarketData]> {
+        self.prices_map.get(ticker).map(Vec::as_slice)
+    }
+
+    pub fn is_expired(&self, market_data: &MarketData) -> bool {
+        let ttl = self
+            .provider_ttls
+            .get(&market_data.provider)
+            .unwrap_or(&self.ttl);
+        market_data.timestamp <= Utc::now().timestamp() - (ttl.as_secs() as i64)
+    }
+
+    fn aggregate_prices(&self, prices: &[MarketData]) -> Option<AggregatedPrice> {
+        let mut filtered_prices = prices
+            .iter()
+            .filter(|price| !self.is_expired(price))
             .cloned()
-            .map(|prices| -> Option<MarketData> {
-                let filtered_prices = prices
-                    .iter()
-                    .filter(|price| {
-                        price.timestamp > Utc::now().timestamp() - (self.ttl.as_secs() as i64)
-                    })
-                    .cloned()
-                    .collect::<Vec<_>>();
-
-                if filtered_prices.len() == 1 {
-                    filtered_prices.get(0).cloned()
-                } else {
-                    let volume: BigDecimal = filtered_prices
-                        .iter()
-                        .map(|price| price.volume.clone())
-                        .sum();
-
-                    filtered_prices.iter().fold(None, |avg, data| match avg {
-                        Some(mut avg_price) => {
-                            if data.timestamp > avg_price.timestamp {
-                                avg_price.timestamp = data.timestamp;
-                            }
-                            avg_price.price +=
-                                data.volume.clone() / volume.clone() * data.price.clone();
-                            avg_price.volume += data.volume.clone();
-                            Some(avg_price)
-                        }
-                        None => Some(MarketData {
-                            provider: data.provider.clone(),
-                            ticker: data.ticker.clone(),
-                            price: data.volume.clone() / volume.clone() * data.price.clone(),
-                            volume: data.volume.clone(),
-                            timestamp: data.timestamp,
-                        }),
-                    })
-                }
-            })
-            .collect::<Option<Vec<_>>>()
+            .collect::<Vec<_>>();
+        if let Some(pinned) = filtered_prices
+            .iter()
+            .find(|price| self.pinning_providers.contains(&price.provider))
         {
-            Some(prices) => Ok(prices),
-            None => Ok(vec![]),
+            filtered_prices = vec![pinned.clone()];
         }
+        let sources = filtered_prices
+            .iter()
+            .map(|price| price.provider.clone())
+            .collect();
+
+        let data = if filtered_prices.len() == 1 {
+            filtered_prices.first().cloned()
+        } else {
+            let volume: BigDecimal = filtered_prices
+                .iter()
+                .map(|price| price.volume.clone())
+                .sum();
+
+            filtered_prices
+                .iter()
+                .fold(None, |avg: Option<MarketData>, data| match avg {
+                    Some(mut avg_price) => {
+                        if data.timestamp > avg_price.timestamp {
+                            avg_price.timestamp = data.timestamp;
+                        }
+                        avg_price.price +=
+                            data.volume.clone() / volume.clone() * data.price.clone();
+                        avg_price.volume += data.volume.clone();
+                        // candle is aggregated only if every source provides it
+                        avg_price.candle = match (&avg_price.candle, &data.candle) {
+                            (Some(avg_candle), Some(candle)) => {
+                                Some(avg_candle.add(&candle.weighted(&(&data.volume / &volume))))
+                            }
+                            _ => None,
+                        };
+                        Some(avg_price)
+                    }
+                    None => Some(MarketData {
+                        provider: data.provider.clone(),
+                        ticker: data.ticker.clone(),
+                        price: data.volume.clone() / volume.clone() * data.price.clone(),
+                        volume: data.volume.clone(),
+                        timestamp: data.timestamp,
+                        book: None,
+                        candle: data
+                            .candle
+                            .as_ref()
+                            .map(|candle| candle.weighted(&(&data.volume / &volume))),
+                        latency_ms: None,
+                    }),
+                })
+        };
+        data.map(|data| AggregatedPrice { data, sources })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::PriceAggregator;
-    use crate::collector::MarketData;
+    use crate::collector::{Candle, MarketData};
+    use crate::fixtures;
     use bigdecimal::{BigDecimal, FromPrimitive};
     use chrono::Utc;
     use std::str::FromStr;
@@ -104,7 +182,9 @@ mod tests {
     #[test]
     fn empty() {
         let ttl = Duration::from_secs(1);
-        assert!(PriceAggregator::new(&ttl).aggregate().unwrap().is_empty());
+        let (fresh_prices, round_prices) = PriceAggregator::new(&ttl).aggregate();
+        assert!(fresh_prices.is_empty());
+        assert!(round_prices.is_empty());
     }
 
     #[test]
@@ -117,6 +197,9 @@ mod tests {
             price: BigDecimal::from_f64(40000.0).unwrap(),
             volume: BigDecimal::from_f64(10.0).unwrap(),
             timestamp: Utc::now().timestamp() - 120,
+            book: None,
+            candle: None,
+            latency_ms: None,
         };
         let bitfinex = MarketData {
             provider: "bitfinex".to_string(),
@@ -124,13 +207,68 @@ mod tests {
             price: BigDecimal::from_f64(40000.0).unwrap(),
             volume: BigDecimal::from_f64(10.0).unwrap(),
             timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
         };
 
         aggregator.consume(&binance);
         aggregator.consume(&bitfinex);
-        let result = aggregator.aggregate().unwrap();
+        assert!(aggregator.is_expired(&binance));
+        assert_eq!(aggregator.quotes("BTC").unwrap().len(), 2);
+        assert_eq!(aggregator.price("BTC").unwrap().data, bitfinex);
+        assert!(aggregator.price("ETH").is_none());
+        let (_, result) = aggregator.aggregate();
         assert_eq!(result.len(), 1);
-        assert!(vec![bitfinex.clone()].iter().eq(result.iter()));
+        assert!([bitfinex.clone()]
+            .iter()
+            .eq(result.iter().map(|price| &price.data)));
+        assert_eq!(result[0].sources, vec!["bitfinex"]);
+    }
+
+    #[test]
+    fn stale_ticker() {
+        let ttl = Duration::from_secs(60);
+        let mut aggregator = PriceAggregator::new(&ttl);
+        let market_data = |ticker: &str, age: i64| {
+            fixtures::market_data(
+                "binance",
+                ticker,
+                BigDecimal::from(1),
+                BigDecimal::from(1),
+                Utc::now().timestamp() - age,
+            )
+        };
+
+        aggregator.consume(&market_data("BTC", 0));
+        aggregator.consume(&market_data("ETH", 120));
+        let (fresh_prices, round_prices) = aggregator.aggregate();
+        assert_eq!(fresh_prices.len(), 1);
+        assert_eq!(fresh_prices[0].data.ticker, "BTC");
+        assert!(round_prices.is_empty());
+
+        aggregator.consume(&market_data("ETH", 0));
+        let (fresh_prices, round_prices) = aggregator.aggregate();
+        assert_eq!(fresh_prices.len(), 2);
+        assert_eq!(round_prices, fresh_prices);
+    }
+
+    #[test]
+    fn provider_ttl() {
+        let ttl = Duration::from_secs(60);
+        let mut aggregator = PriceAggregator::new(&ttl);
+        aggregator.set_provider_ttl("ecb", &Duration::from_secs(86400));
+        let ecb = fixtures::market_data(
+            "ecb",
+            "EURUSD",
+            BigDecimal::from_str("1.1").unwrap(),
+            BigDecimal::from(1),
+            Utc::now().timestamp() - 3600,
+        );
+
+        aggregator.consume(&ecb);
+        let (_, result) = aggregator.aggregate();
+        assert!([ecb].iter().eq(result.iter().map(|price| &price.data)));
     }
 
     #[test]
@@ -143,6 +281,9 @@ mod tests {
             price: BigDecimal::from_str("42580.02").unwrap(),
             volume: BigDecimal::from_str("18555.70986").unwrap(),
             timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
         };
         let bitfinex = MarketData {
             provider: "bitfinex".to_string(),
@@ -150,17 +291,87 @@ mod tests {
             price: BigDecimal::from_f64(42562.0).unwrap(),
             volume: BigDecimal::from_f64(1929.42519104).unwrap(),
             timestamp: Utc::now().timestamp() - 1,
+            book: None,
+            candle: None,
+            latency_ms: None,
         };
 
         aggregator.consume(&binance);
         aggregator.consume(&bitfinex);
-        let result = aggregator.aggregate().unwrap();
+        let (_, result) = aggregator.aggregate();
         assert_eq!(result.len(), 1);
-        let data = result.get(0).unwrap();
+        let data = &result.first().unwrap().data;
         assert_eq!(data.provider, binance.provider);
         assert_eq!(data.ticker, binance.ticker);
         assert_eq!(data.volume, BigDecimal::from_str("20485.13505104").unwrap());
         assert_eq!(data.price, BigDecimal::from_str("42578.3227574943531715796949408716305856862537106332767936007037519948040614906956044719693742682527181817900000000000").unwrap());
         assert_eq!(data.timestamp, binance.timestamp);
+        assert_eq!(result[0].sources, vec!["binance", "bitfinex"]);
+    }
+
+    #[test]
+    fn weighted_candle() {
+        let ttl = Duration::from_secs(60);
+        let mut aggregator = PriceAggregator::new(&ttl);
+        let candle = |open: &str, high: &str, low: &str, close: &str| Candle {
+            open: BigDecimal::from_str(open).unwrap(),
+            high: BigDecimal::from_str(high).unwrap(),
+            low: BigDecimal::from_str(low).unwrap(),
+            close: BigDecimal::from_str(close).unwrap(),
+        };
+        let mut binance = fixtures::market_data(
+            "binance",
+            "BTC",
+            BigDecimal::from(100),
+            BigDecimal::from(3),
+            Utc::now().timestamp(),
+        );
+        binance.candle = Some(candle("90", "110", "80", "100"));
+        let mut bitfinex = fixtures::market_data(
+            "bitfinex",
+            "BTC",
+            BigDecimal::from(104),
+            BigDecimal::from(1),
+            Utc::now().timestamp(),
+        );
+        bitfinex.candle = Some(candle("94", "114", "84", "104"));
+
+        aggregator.consume(&binance);
+        aggregator.consume(&bitfinex);
+        let (_, result) = aggregator.aggregate();
+        let data = &result.first().unwrap().data;
+        assert_eq!(data.price, BigDecimal::from_str("101").unwrap());
+        assert_eq!(
+            data.candle.clone().unwrap(),
+            candle("91", "111", "81", "101")
+        );
+    }
+
+    #[test]
+    fn pinned() {
+        let ttl = Duration::from_secs(60);
+        let mut aggregator = PriceAggregator::new(&ttl);
+        aggregator.set_pinning_provider("static");
+        let market_data = |provider: &str, price: &str, age: i64| {
+            fixtures::market_data(
+                provider,
+                "USDT",
+                BigDecimal::from_str(price).unwrap(),
+                BigDecimal::from(10),
+                Utc::now().timestamp() - age,
+            )
+        };
+
+        aggregator.consume(&market_data("binance", "0.98", 0));
+        aggregator.consume(&market_data("static", "1", 0));
+        let price = aggregator.price("USDT").unwrap();
+        assert_eq!(price.data.price, BigDecimal::from(1));
+        assert_eq!(price.sources, vec!["static"]);
+
+        // expired pinned price doesn't override
+        aggregator.consume(&market_data("static", "1", 120));
+        let price = aggregator.price("USDT").unwrap();
+        assert_eq!(price.data.price, BigDecimal::from_str("0.98").unwrap());
+        assert_eq!(price.sources, vec!["binance"]);
     }
 }
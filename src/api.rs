@@ -0,0 +1,248 @@
+use crate::aggregator::{AggregatedPrice, PriceAggregator};
+use crate::collector::{Candle, MarketData};
+use crate::config::ApiConfig;
+use crate::error::Error;
+use crate::metrics;
+use axum::extract::{Extension, Path};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use serde::Serialize;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+type SharedAggregator = Arc<RwLock<PriceAggregator>>;
+
+#[derive(Debug, Serialize)]
+struct PriceResponse {
+    ticker: String,
+    price: BigDecimal,
+    volume: BigDecimal,
+    timestamp: i64,
+    age_secs: i64,
+    candle: Option<Candle>,
+    sources: Vec<String>,
+}
+
+impl From<AggregatedPrice> for PriceResponse {
+    fn from(price: AggregatedPrice) -> Self {
+        PriceResponse {
+            age_secs: age_secs(price.data.timestamp),
+            ticker: price.data.ticker,
+            price: price.data.price.normalized(),
+            volume: price.data.volume.normalized(),
+            timestamp: price.data.timestamp,
+            candle: price.data.candle,
+            sources: price.sources,
+        }
+    }
+}
+
+// raw provider quote
+#[derive(Debug, Serialize)]
+struct SourceResponse {
+    #[serde(flatten)]
+    data: MarketData,
+    age_secs: i64,
+    // not used in aggregation
+    expired: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, Json<ErrorResponse>)>;
+
+pub fn router(aggregator: SharedAggregator) -> Router {
+    Router::new()
+        .route("/prices", get(get_prices))
+        .route("/prices/:ticker", get(get_price))
+        .route("/prices/:ticker/sources", get(get_sources))
+        .route("/metrics", get(get_metrics))
+        .layer(Extension(aggregator))
+}
+
+// serves latest aggregated prices:
+// `GET /prices`, `GET /prices/{ticker}`, `GET /prices/{ticker}/sources`,
+// and prometheus metrics: `GET /metrics`,
+// address is bound on call, so address errors fail startup
+pub fn serve(
+    config: &ApiConfig,
+    aggregator: SharedAggregator,
+) -> Result<impl Future<Output = Result<(), Error>>, Error> {
+    let address: SocketAddr = config
+        .address
+        .parse()
+        .map_err(|e| Error::HttpServer(format!("invalid api address: {}", e)))?;
+    let server = axum::Server::try_bind(&address)
+        .map_err(|e| Error::HttpServer(e.to_string()))?
+        .serve(router(aggregator).into_make_service());
+    Ok(async move { server.await.map_err(|e| Error::HttpServer(e.to_string())) })
+}
+
+async fn get_prices(
+    Extension(aggregator): Extension<SharedAggregator>,
+) -> Json<Vec<PriceResponse>> {
+    Json(
+        aggregator
+            .read()
+            .await
+            .prices()
+            .into_iter()
+            .map(PriceResponse::from)
+            .collect(),
+    )
+}
+
+async fn get_price(
+    Path(ticker): Path<String>,
+    Extension(aggregator): Extension<SharedAggregator>,
+) -> ApiResult<PriceResponse> {
+    match aggregator.read().await.price(&ticker) {
+        Some(price) => Ok(Json(price.into())),
+        None => Err(not_found(&ticker)),
+    }
+}
+
+async fn get_sources(
+    Path(ticker): Path<String>,
+    Extension(aggregator): Extension<SharedAggregator>,
+) -> ApiResult<Vec<SourceResponse>> {
+    let aggregator = aggregator.read().await;
+    match aggregator.quotes(&ticker) {
+        Some(quotes) => Ok(Json(
+            quotes
+                .iter()
+                .map(|data| SourceResponse {
+                    data: data.clone(),
+                    age_secs: age_secs(data.timestamp),
+                    expired: aggregator.is_expired(data),
+                })
+                .collect(),
+        )),
+        None => Err(not_found(&ticker)),
+    }
+}
+
+async fn get_metrics() -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    metrics::encode().map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })
+}
+
+fn not_found(ticker: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: format!("no price of {}", ticker),
+        }),
+    )
+}
+
+fn age_secs(timestamp: i64) -> i64 {
+    Utc::now().timestamp() - timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{router, serve};
+    use crate::aggregator::PriceAggregator;
+    use crate::collector::MarketData;
+    use crate::config::ApiConfig;
+    use crate::fixtures;
+    use bigdecimal::BigDecimal;
+    use chrono::Utc;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+
+    fn market_data(provider: &str, price: i64, age: i64) -> MarketData {
+        let mut data = fixtures::market_data(
+            provider,
+            "BTC",
+            BigDecimal::from(price),
+            BigDecimal::from(1),
+            Utc::now().timestamp() - age,
+        );
+        data.latency_ms = Some(10);
+        data
+    }
+
+    #[tokio::test]
+    async fn prices() {
+        let mut aggregator = PriceAggregator::new(&Duration::from_secs(60));
+        aggregator.consume(&market_data("binance", 40000, 0));
+        aggregator.consume(&market_data("bitfinex", 42000, 0));
+        aggregator.consume(&market_data("waves", 10, 120));
+
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(router(Arc::new(RwLock::new(aggregator))).into_make_service());
+        let endpoint = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+
+        let prices: Value = reqwest::get(format!("{}/prices", endpoint))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(prices[0]["ticker"], "BTC");
+        assert_eq!(prices[0]["price"], "41000");
+        assert_eq!(
+            prices[0]["sources"],
+            serde_json::json!(["binance", "bitfinex"])
+        );
+
+        let price: Value = reqwest::get(format!("{}/prices/BTC", endpoint))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        // age may tick between requests
+        assert_eq!(price["ticker"], prices[0]["ticker"]);
+        assert_eq!(price["price"], prices[0]["price"]);
+        assert_eq!(price["sources"], prices[0]["sources"]);
+
+        let sources: Value = reqwest::get(format!("{}/prices/BTC/sources", endpoint))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(sources.as_array().unwrap().len(), 3);
+        assert_eq!(sources[2]["provider"], "waves");
+        assert_eq!(sources[2]["expired"], true);
+        assert!(sources[2]["age_secs"].as_i64().unwrap() >= 120);
+
+        let res = reqwest::get(format!("{}/prices/ETH", endpoint))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn bind() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = ApiConfig {
+            enabled: true,
+            address: listener.local_addr().unwrap().to_string(),
+        };
+        let aggregator = Arc::new(RwLock::new(PriceAggregator::new(&Duration::from_secs(60))));
+        assert!(serve(&config, aggregator).is_err());
+    }
+}
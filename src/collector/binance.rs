@@ -1,16 +1,52 @@
-use super::{MarketData, MarketDataCollector};
-use crate::config::{CollectorConfig, Ticker};
+use super::schedule::TickerSchedule;
+use super::{
+    poll_tickers, vwap, Book, Candle, MarketData, MarketDataCollector, TickerCollector, Trade,
+};
+use crate::config::{CollectorConfig, CollectorMode, Ticker};
 use crate::error::Error;
+use crate::http::build_client;
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use chrono::Utc;
+use futures::future::try_join;
 use pepe_log::error;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::de::IgnoredAny;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::{sync::mpsc::Sender, time::sleep};
+use tokio::sync::mpsc::Sender;
+use tokio::time::sleep;
 
 const BINANCE_PROVIDER_NAME: &str = "binance";
+const BINANCE_AGG_TRADES_LIMIT: usize = 1000;
+// window trades are fetched page by page, too liquid pairs fail instead of going stale
+const BINANCE_AGG_TRADES_MAX_PAGES: usize = 10;
+const BINANCE_TRADING_STATUS: &str = "TRADING";
+// request issues error codes, like invalid symbol or bad parameter
+const BINANCE_PERMANENT_ERROR_CODES: RangeInclusive<i64> = -1199..=-1100;
+// no orders on book, the only transient request issue
+const BINANCE_NO_DEPTH_ERROR_CODE: i64 = -1112;
+
+#[derive(Debug, Deserialize)]
+struct ResponseSymbol {
+    symbol: String,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseExchangeInfo {
+    symbols: Vec<ResponseSymbol>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    code: i64,
+    msg: String,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Response24h {
@@ -20,32 +56,160 @@ struct Response24h {
     volume: BigDecimal,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseBookTicker {
+    #[serde(rename(deserialize = "bidPrice"))]
+    bid_price: BigDecimal,
+    #[serde(rename(deserialize = "askPrice"))]
+    ask_price: BigDecimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseAggTrade {
+    #[serde(rename(deserialize = "a"))]
+    id: u64,
+    #[serde(rename(deserialize = "T"))]
+    time: i64,
+    #[serde(rename(deserialize = "p"))]
+    price: BigDecimal,
+    #[serde(rename(deserialize = "q"))]
+    quantity: BigDecimal,
+}
+
+// [open time, open, high, low, close, volume, close time, quote volume, ...]
+#[derive(Debug, Deserialize)]
+struct ResponseKline(
+    IgnoredAny,
+    BigDecimal,
+    BigDecimal,
+    BigDecimal,
+    BigDecimal,
+    IgnoredAny,
+    IgnoredAny,
+    BigDecimal,
+    IgnoredAny,
+    IgnoredAny,
+    IgnoredAny,
+    IgnoredAny,
+);
+
 #[derive(Debug, Clone)]
 pub struct BinanceMarketDataCollector {
     endpoint: String,
-    tickers: Vec<Ticker>,
-    batch_delay: Duration,
+    mode: CollectorMode,
+    max_spread: Option<BigDecimal>,
+    window: Duration,
+    interval: String,
+    schedule: TickerSchedule,
     request_delay: Duration,
+    max_in_flight: usize,
+    validate_interval: Duration,
     client: Client,
 }
 
 impl BinanceMarketDataCollector {
-    pub fn new(config: &CollectorConfig) -> Self {
-        BinanceMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        Ok(BinanceMarketDataCollector {
             endpoint: config.endpoint.clone(),
-            tickers: config.tickers.clone(),
-            batch_delay: config.delay.batch.into(),
+            mode: config.mode,
+            max_spread: config.max_spread.clone(),
+            window: config.window.into(),
+            interval: config.interval.clone(),
+            schedule: TickerSchedule::new(&config.tickers, &config.delay)?,
             request_delay: config.delay.request.into(),
-            client: Client::new(),
-        }
+            max_in_flight: config.max_in_flight.max(1),
+            validate_interval: config.validate_interval.into(),
+            client: build_client(&config.http)?,
+        })
     }
 
     async fn get_market_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        match self.mode {
+            CollectorMode::Ticker => self.get_ticker_data(ticker).await,
+            CollectorMode::Book => self.get_book_data(ticker).await,
+            CollectorMode::Trades => self.get_trades_data(ticker).await,
+            CollectorMode::Klines => self.get_klines_data(ticker).await,
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        let res = self.client.get(url).send().await?;
+        let status = res.status();
+        decode_response(status, &res.bytes().await?)
+    }
+
+    async fn get_trading_symbols(&self) -> Result<HashSet<String>, Error> {
+        let url = format!("{}/api/v3/exchangeInfo", self.endpoint);
+        let res: ResponseExchangeInfo = self.get_json(url).await?;
+        Ok(res
+            .symbols
+            .into_iter()
+            .filter(|symbol| symbol.status == BINANCE_TRADING_STATUS)
+            .map(|symbol| symbol.symbol)
+            .collect())
+    }
+
+    async fn get_24h(&self, ticker: &Ticker) -> Result<Response24h, Error> {
         let url = format!(
             "{}/api/v3/ticker/24hr?symbol={}",
             self.endpoint, ticker.ticker
         );
-        let res: Response24h = self.client.get(url).send().await?.json().await?;
+        self.get_json(url).await
+    }
+
+    async fn get_book_ticker(&self, ticker: &Ticker) -> Result<ResponseBookTicker, Error> {
+        let url = format!(
+            "{}/api/v3/ticker/bookTicker?symbol={}",
+            self.endpoint, ticker.ticker
+        );
+        self.get_json(url).await
+    }
+
+    // trades of the window, time bounded request returns only the oldest `limit` trades,
+    // so the rest of the window is paged by trade id
+    async fn get_agg_trades(&self, ticker: &Ticker) -> Result<Vec<ResponseAggTrade>, Error> {
+        let end_time = Utc::now().timestamp_millis();
+        let mut url = format!(
+            "{}/api/v3/aggTrades?symbol={}&startTime={}&endTime={}&limit={}",
+            self.endpoint,
+            ticker.ticker,
+            end_time - self.window.as_millis() as i64,
+            end_time,
+            BINANCE_AGG_TRADES_LIMIT
+        );
+        let mut trades = vec![];
+        for _ in 0..BINANCE_AGG_TRADES_MAX_PAGES {
+            let page: Vec<ResponseAggTrade> = self.get_json(url).await?;
+            let next_id = next_agg_trade_id(&page, end_time);
+            trades.extend(page.into_iter().filter(|trade| trade.time <= end_time));
+            match next_id {
+                Some(id) => {
+                    url = format!(
+                        "{}/api/v3/aggTrades?symbol={}&fromId={}&limit={}",
+                        self.endpoint, ticker.ticker, id, BINANCE_AGG_TRADES_LIMIT
+                    )
+                }
+                None => return Ok(trades),
+            }
+        }
+        Err(Error::Collector(format!(
+            "binance {} has more than {} trades in window",
+            ticker.ticker,
+            BINANCE_AGG_TRADES_LIMIT * BINANCE_AGG_TRADES_MAX_PAGES
+        )))
+    }
+
+    // two last klines, the first one is closed
+    async fn get_klines(&self, ticker: &Ticker) -> Result<Vec<ResponseKline>, Error> {
+        let url = format!(
+            "{}/api/v3/klines?symbol={}&interval={}&limit=2",
+            self.endpoint, ticker.ticker, self.interval
+        );
+        self.get_json(url).await
+    }
+
+    async fn get_ticker_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let res = self.get_24h(&ticker).await?;
 
         Ok(MarketData {
             provider: BINANCE_PROVIDER_NAME.to_string(),
@@ -61,30 +225,162 @@ impl BinanceMarketDataCollector {
             },
             volume: res.volume,
             timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
+        })
+    }
+
+    // mid price of the top of book, 24h volume is kept as weight
+    async fn get_book_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let (book, stats) = try_join(self.get_book_ticker(&ticker), self.get_24h(&ticker)).await?;
+        let book = Book::new(book.bid_price, book.ask_price, ticker.inverted)?;
+        book.check_spread(&self.max_spread)?;
+
+        Ok(MarketData {
+            provider: BINANCE_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker
+            } else {
+                ticker.alias
+            },
+            price: book.mid(),
+            volume: stats.volume,
+            timestamp: Utc::now().timestamp(),
+            book: Some(book),
+            candle: None,
+            latency_ms: None,
+        })
+    }
+
+    // vwap of aggregated trades over window
+    async fn get_trades_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let trades = self
+            .get_agg_trades(&ticker)
+            .await?
+            .into_iter()
+            .map(|trade| Trade {
+                price: trade.price,
+                amount: trade.quantity,
+            })
+            .collect::<Vec<_>>();
+        let (price, volume) = vwap(&trades)?;
+
+        Ok(MarketData {
+            provider: BINANCE_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker
+            } else {
+                ticker.alias
+            },
+            price: if ticker.inverted {
+                price.inverse()
+            } else {
+                price
+            },
+            volume,
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
+        })
+    }
+
+    // ohlc of the last closed kline, close is used as price
+    async fn get_klines_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let kline = self
+            .get_klines(&ticker)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Collector(String::from("no klines")))?;
+        let candle = Candle::new(kline.1, kline.2, kline.3, kline.4, ticker.inverted);
+
+        Ok(MarketData {
+            provider: BINANCE_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker
+            } else {
+                ticker.alias
+            },
+            price: candle.close.clone(),
+            volume: kline.7,
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: Some(candle),
+            latency_ms: None,
         })
     }
 }
 
+// first trade id of the next page, if the page is full and doesn't reach window end
+fn next_agg_trade_id(page: &[ResponseAggTrade], end_time: i64) -> Option<u64> {
+    match page.last() {
+        Some(last) if page.len() >= BINANCE_AGG_TRADES_LIMIT && last.time < end_time => {
+            Some(last.id + 1)
+        }
+        _ => None,
+    }
+}
+
+// decodes response or binance error envelope
+fn decode_response<T: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<T, Error> {
+    if status.is_success() {
+        return Ok(serde_json::from_slice(body)?);
+    }
+
+    match serde_json::from_slice::<ResponseError>(body) {
+        Ok(e) => Err(Error::Exchange {
+            exchange: BINANCE_PROVIDER_NAME.to_string(),
+            status: status.as_u16(),
+            code: e.code,
+            message: e.msg,
+            permanent: BINANCE_PERMANENT_ERROR_CODES.contains(&e.code)
+                && e.code != BINANCE_NO_DEPTH_ERROR_CODE,
+        }),
+        Err(_) => Err(Error::Collector(format!("binance http status {}", status))),
+    }
+}
+
+#[async_trait]
+impl TickerCollector for BinanceMarketDataCollector {
+    fn name(&self) -> &'static str {
+        BINANCE_PROVIDER_NAME
+    }
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        self.get_market_data(ticker).await
+    }
+}
+
 #[async_trait]
 impl MarketDataCollector for BinanceMarketDataCollector {
-    async fn collect(&self, tx: Sender<MarketData>) {
-        loop {
-            for ticker in &self.tickers {
-                match self.get_market_data(ticker.clone()).await {
-                    Ok(market_data) => {
-                        match tx.send(market_data).await {
-                            Ok(_) => {}
-                            Err(e) => error!("can't push market from binance to channel: {}", e),
-                        };
-                    }
-                    Err(e) => {
-                        error!("can't get market from binance: {}", e);
-                    }
-                };
-
-                sleep(self.request_delay).await
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(collector.validate_interval).await;
+                if let Err(e) = collector.validate().await {
+                    error!("binance validation failed: {}", e);
+                }
             }
-            sleep(self.batch_delay).await
+        });
+        let schedule = self.schedule.clone();
+        let (max_in_flight, request_delay) = (self.max_in_flight, self.request_delay);
+        poll_tickers(self, schedule, max_in_flight, request_delay, tx).await
+    }
+
+    async fn validate(&self) -> Result<(), Error> {
+        let not_trading = self
+            .schedule
+            .update_trading(&self.get_trading_symbols().await?);
+        if not_trading.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Collector(format!(
+                "binance tickers are not trading: {}",
+                not_trading.join(", ")
+            )))
         }
     }
 }
@@ -94,7 +390,7 @@ fn test_inverse() {
     use ::bigdecimal::*;
     use std::str::FromStr;
 
-    let vals = vec![
+    let vals = [
         ("100", "0.01"),
         ("2", "0.5"),
         (".2", "5"),
@@ -109,3 +405,51 @@ fn test_inverse() {
         assert_eq!(i.inverse(), a);
     }
 }
+
+#[test]
+fn test_decode_error() {
+    let err = decode_response::<Response24h>(
+        StatusCode::BAD_REQUEST,
+        br#"{"code":-1121,"msg":"Invalid symbol."}"#,
+    )
+    .unwrap_err();
+    assert!(err.is_permanent());
+    assert_eq!(
+        err.to_string(),
+        "binance error -1121: Invalid symbol. (http status 400)"
+    );
+
+    let err = decode_response::<Response24h>(
+        StatusCode::TOO_MANY_REQUESTS,
+        br#"{"code":-1003,"msg":"Too many requests."}"#,
+    )
+    .unwrap_err();
+    assert!(!err.is_permanent());
+}
+
+#[test]
+fn test_agg_trades_pages() {
+    let page = |count: usize, last_time: i64| {
+        (0..count)
+            .map(|id| ResponseAggTrade {
+                id: id as u64,
+                time: last_time - (count - id - 1) as i64,
+                price: BigDecimal::from(1),
+                quantity: BigDecimal::from(1),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(next_agg_trade_id(&[], 100), None);
+    // window is complete
+    assert_eq!(next_agg_trade_id(&page(10, 99), 100), None);
+    // full page ends before window end
+    assert_eq!(
+        next_agg_trade_id(&page(BINANCE_AGG_TRADES_LIMIT, 99), 100),
+        Some(BINANCE_AGG_TRADES_LIMIT as u64)
+    );
+    // full page reaches window end
+    assert_eq!(
+        next_agg_trade_id(&page(BINANCE_AGG_TRADES_LIMIT, 100), 100),
+        None
+    );
+}
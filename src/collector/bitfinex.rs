@@ -1,51 +1,167 @@
-use super::{MarketData, MarketDataCollector};
-use crate::config::{CollectorConfig, Ticker};
+use super::schedule::TickerSchedule;
+use super::{
+    poll_tickers, vwap, Book, Candle, MarketData, MarketDataCollector, TickerCollector, Trade,
+};
+use crate::config::{CollectorConfig, CollectorMode, Ticker};
 use crate::error::Error;
+use crate::http::build_client;
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use bigdecimal::FromPrimitive;
 use chrono::Utc;
 use pepe_log::error;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 use tokio::time::sleep;
 
 const BITFINEX_PROVIDER_NAME: &str = "bitfinex";
+const BITFINEX_TRADES_LIMIT: usize = 10000;
+// window trades are fetched page by page, too liquid pairs fail instead of going stale
+const BITFINEX_TRADES_MAX_PAGES: usize = 10;
+const BITFINEX_ERROR_EVENT: &str = "error";
+// invalid request parameters, like unknown symbol
+const BITFINEX_PARAMS_ERROR_CODE: i64 = 10020;
 
 #[derive(Debug, Clone)]
 pub struct BitfinexMarketDataCollector {
     endpoint: String,
-    tickers: Vec<Ticker>,
-    batch_delay: Duration,
+    mode: CollectorMode,
+    max_spread: Option<BigDecimal>,
+    window: Duration,
+    interval: String,
+    schedule: TickerSchedule,
     request_delay: Duration,
+    max_in_flight: usize,
+    validate_interval: Duration,
     client: Client,
 }
 
 impl BitfinexMarketDataCollector {
-    pub fn new(config: &CollectorConfig) -> Self {
-        BitfinexMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        Ok(BitfinexMarketDataCollector {
             endpoint: config.endpoint.clone(),
-            tickers: config.tickers.clone(),
-            batch_delay: config.delay.batch.into(),
+            mode: config.mode,
+            max_spread: config.max_spread.clone(),
+            window: config.window.into(),
+            interval: config.interval.clone(),
+            schedule: TickerSchedule::new(&config.tickers, &config.delay)?,
             request_delay: config.delay.request.into(),
-            client: Client::new(),
-        }
+            max_in_flight: config.max_in_flight.max(1),
+            validate_interval: config.validate_interval.into(),
+            client: build_client(&config.http)?,
+        })
     }
 
     async fn get_market_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        match self.mode {
+            CollectorMode::Ticker => self.get_ticker_data(ticker).await,
+            CollectorMode::Book => self.get_book_data(ticker).await,
+            CollectorMode::Trades => self.get_trades_data(ticker).await,
+            CollectorMode::Klines => self.get_candles_data(ticker).await,
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        let res = self.client.get(url).send().await?;
+        let status = res.status();
+        decode_response(status, &res.bytes().await?)
+    }
+
+    // listed exchange pairs, `[[PAIR, ...]]`
+    async fn get_trading_symbols(&self) -> Result<HashSet<String>, Error> {
+        let url = format!("{}/v2/conf/pub:list:pair:exchange", self.endpoint);
+        let res: Vec<Vec<String>> = self.get_json(url).await?;
+        Ok(res.into_iter().flatten().collect())
+    }
+
+    // `[BID, BID_SIZE, ASK, ASK_SIZE, DAILY_CHANGE, DAILY_CHANGE_RELATIVE, LAST_PRICE, VOLUME, ...]`
+    async fn get_ticker(&self, ticker: &Ticker) -> Result<Vec<f64>, Error> {
         let url = format!("{}/v2/ticker/t{}", self.endpoint, ticker.ticker);
-        let res: Vec<f64> = self.client.get(url).send().await?.json().await?;
-        let price = res
-            .get(6)
-            .ok_or_else(|| Error::Provider(String::from("can't decode price")))
-            .map(|num| BigDecimal::from_f64(*num))?
-            .ok_or_else(|| Error::Collector(String::from("can't decode price format")))?;
-        let volume = res
-            .get(7)
-            .ok_or_else(|| Error::Provider(String::from("can't decode volume")))
-            .map(|num| BigDecimal::from_f64(*num))?
-            .ok_or_else(|| Error::Collector(String::from("can't decode volume format")))?;
+        self.get_json(url).await
+    }
+
+    async fn get_ticker_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let res = self.get_ticker(&ticker).await?;
+        let price = decode_field(&res, 6, "price")?;
+        let price = if ticker.inverted {
+            price.inverse()
+        } else {
+            price
+        };
+        Ok(market_data(
+            ticker,
+            price,
+            decode_field(&res, 7, "volume")?,
+            None,
+        ))
+    }
+
+    // mid price of the top of book, 24h volume is kept as weight
+    async fn get_book_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let res = self.get_ticker(&ticker).await?;
+        let book = Book::new(
+            decode_field(&res, 0, "bid")?,
+            decode_field(&res, 2, "ask")?,
+            ticker.inverted,
+        )?;
+        book.check_spread(&self.max_spread)?;
+        Ok(market_data(
+            ticker,
+            book.mid(),
+            decode_field(&res, 7, "volume")?,
+            Some(book),
+        ))
+    }
+
+    // window trades, newest first, pages go back by `end` of the oldest trade,
+    // trades of that millisecond are repeated on the next page and skipped by id
+    async fn get_trades(&self, ticker: &Ticker) -> Result<Vec<Vec<f64>>, Error> {
+        let mut end = Utc::now().timestamp_millis();
+        let start = end - self.window.as_millis() as i64;
+        let mut ids = HashSet::new();
+        let mut trades = vec![];
+        for _ in 0..BITFINEX_TRADES_MAX_PAGES {
+            let url = format!(
+                "{}/v2/trades/t{}/hist?start={}&end={}&limit={}",
+                self.endpoint, ticker.ticker, start, end, BITFINEX_TRADES_LIMIT
+            );
+            let page: Vec<Vec<f64>> = self.get_json(url).await?;
+            let next_end = next_trades_end(&page);
+            trades.extend(
+                page.into_iter()
+                    .filter(|trade| trade.first().is_some_and(|id| ids.insert(*id as i64))),
+            );
+            match next_end {
+                Some(next_end) => end = next_end,
+                None => return Ok(trades),
+            }
+        }
+        Err(Error::Collector(format!(
+            "bitfinex {} has more than {} trades in window",
+            ticker.ticker,
+            BITFINEX_TRADES_LIMIT * BITFINEX_TRADES_MAX_PAGES
+        )))
+    }
+
+    // vwap of trades over window, trade is [ID, MTS, AMOUNT, PRICE]
+    async fn get_trades_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let trades = self
+            .get_trades(&ticker)
+            .await?
+            .iter()
+            .map(|trade| {
+                Ok(Trade {
+                    price: decode_field(trade, 3, "trade price")?,
+                    amount: decode_field(trade, 2, "trade amount")?.abs(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let (price, volume) = vwap(&trades)?;
+
         Ok(MarketData {
             provider: BITFINEX_PROVIDER_NAME.to_string(),
             ticker: if ticker.alias.trim().is_empty() {
@@ -60,30 +176,181 @@ impl BitfinexMarketDataCollector {
             },
             volume,
             timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
         })
     }
+
+    // ohlc of the last closed candle, candle is [MTS, OPEN, CLOSE, HIGH, LOW, VOLUME]
+    async fn get_candles_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let url = format!(
+            "{}/v2/candles/trade:{}:t{}/hist?limit=2",
+            self.endpoint, self.interval, ticker.ticker
+        );
+        let res: Vec<Vec<f64>> = self.get_json(url).await?;
+        let candle = res
+            .get(1)
+            .ok_or_else(|| Error::Collector(String::from("no candles")))?;
+        let close = decode_field(candle, 2, "close")?;
+        let volume = decode_field(candle, 5, "volume")? * &close;
+
+        Ok(MarketData {
+            provider: BITFINEX_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker
+            } else {
+                ticker.alias
+            },
+            price: if ticker.inverted {
+                close.inverse()
+            } else {
+                close.clone()
+            },
+            volume,
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: Some(Candle::new(
+                decode_field(candle, 1, "open")?,
+                decode_field(candle, 3, "high")?,
+                decode_field(candle, 4, "low")?,
+                close,
+                ticker.inverted,
+            )),
+            latency_ms: None,
+        })
+    }
+}
+
+// decodes response or bitfinex `["error", code, message]` envelope
+// time of the oldest trade to end the next page with, if the page is full
+fn next_trades_end(page: &[Vec<f64>]) -> Option<i64> {
+    if page.len() < BITFINEX_TRADES_LIMIT {
+        return None;
+    }
+    page.last()
+        .and_then(|trade| trade.get(1))
+        .map(|mts| *mts as i64)
+}
+
+fn decode_response<T: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<T, Error> {
+    if status.is_success() {
+        if let Ok(data) = serde_json::from_slice(body) {
+            return Ok(data);
+        }
+    }
+
+    match serde_json::from_slice::<(String, i64, String)>(body) {
+        Ok((event, code, message)) if event == BITFINEX_ERROR_EVENT => Err(Error::Exchange {
+            exchange: BITFINEX_PROVIDER_NAME.to_string(),
+            status: status.as_u16(),
+            code,
+            message,
+            permanent: code == BITFINEX_PARAMS_ERROR_CODE,
+        }),
+        _ if status.is_success() => Ok(serde_json::from_slice(body)?),
+        _ => Err(Error::Collector(format!("bitfinex http status {}", status))),
+    }
+}
+
+fn market_data(
+    ticker: Ticker,
+    price: BigDecimal,
+    volume: BigDecimal,
+    book: Option<Book>,
+) -> MarketData {
+    MarketData {
+        provider: BITFINEX_PROVIDER_NAME.to_string(),
+        ticker: if ticker.alias.trim().is_empty() {
+            ticker.ticker
+        } else {
+            ticker.alias
+        },
+        price,
+        volume,
+        timestamp: Utc::now().timestamp(),
+        book,
+        candle: None,
+        latency_ms: None,
+    }
+}
+
+// decode numeric field of ticker response by index
+fn decode_field(res: &[f64], index: usize, name: &str) -> Result<BigDecimal, Error> {
+    res.get(index)
+        .ok_or_else(|| Error::Provider(format!("can't decode {}", name)))
+        .map(|num| BigDecimal::from_f64(*num))?
+        .ok_or_else(|| Error::Collector(format!("can't decode {} format", name)))
+}
+
+#[async_trait]
+impl TickerCollector for BitfinexMarketDataCollector {
+    fn name(&self) -> &'static str {
+        BITFINEX_PROVIDER_NAME
+    }
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        self.get_market_data(ticker).await
+    }
 }
 
 #[async_trait]
 impl MarketDataCollector for BitfinexMarketDataCollector {
-    async fn collect(&self, tx: Sender<MarketData>) {
-        loop {
-            for ticker in &self.tickers {
-                match self.get_market_data(ticker.clone()).await {
-                    Ok(market_data) => {
-                        match tx.send(market_data).await {
-                            Ok(_) => {}
-                            Err(e) => error!("can't push market from bitfinex to channel: {}", e),
-                        };
-                    }
-                    Err(e) => {
-                        error!("can't get market from bitfinex: {}", e);
-                    }
-                };
-
-                sleep(self.request_delay).await
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(collector.validate_interval).await;
+                if let Err(e) = collector.validate().await {
+                    error!("bitfinex validation failed: {}", e);
+                }
             }
-            sleep(self.batch_delay).await
+        });
+        let schedule = self.schedule.clone();
+        let (max_in_flight, request_delay) = (self.max_in_flight, self.request_delay);
+        poll_tickers(self, schedule, max_in_flight, request_delay, tx).await
+    }
+
+    async fn validate(&self) -> Result<(), Error> {
+        let not_trading = self
+            .schedule
+            .update_trading(&self.get_trading_symbols().await?);
+        if not_trading.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Collector(format!(
+                "bitfinex tickers are not trading: {}",
+                not_trading.join(", ")
+            )))
         }
     }
 }
+
+#[test]
+fn test_decode_error() {
+    let err = decode_response::<Vec<f64>>(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        br#"["error",10020,"symbol: invalid"]"#,
+    )
+    .unwrap_err();
+    assert!(err.is_permanent());
+    assert_eq!(
+        err.to_string(),
+        "bitfinex error 10020: symbol: invalid (http status 500)"
+    );
+
+    let res = decode_response::<Vec<f64>>(StatusCode::OK, b"[1.5,2.5]").unwrap();
+    assert_eq!(res, vec![1.5, 2.5]);
+}
+
+#[test]
+fn test_trades_pages() {
+    let page = (0..BITFINEX_TRADES_LIMIT)
+        .map(|id| vec![id as f64, 1650000000000.0 - id as f64, 1.0, 40000.0])
+        .collect::<Vec<_>>();
+    assert_eq!(
+        next_trades_end(&page),
+        Some(1650000000000 - BITFINEX_TRADES_LIMIT as i64 + 1)
+    );
+    assert_eq!(next_trades_end(&page[1..]), None);
+}
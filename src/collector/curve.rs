@@ -0,0 +1,224 @@
+use super::eth::{decode_address, decode_uint, encode_call, EthClient};
+use super::schedule::TickerSchedule;
+use super::{poll_tickers, MarketData, MarketDataCollector, TickerCollector};
+use crate::config::{CollectorConfig, Ticker};
+use crate::error::Error;
+use crate::http::build_client;
+use async_trait::async_trait;
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc::Sender, RwLock};
+
+const CURVE_PROVIDER_NAME: &str = "curve";
+const CURVE_GET_DY_SELECTOR: &str = "0x5e0d443f";
+const CURVE_COINS_SELECTOR: &str = "0xc6610657";
+const CURVE_BALANCES_SELECTOR: &str = "0x4903b0d1";
+const CURVE_MAX_COINS: usize = 8;
+
+// curve pool ticker `POOL_ADDRESS:i:j`, price of coin i in coin j
+#[derive(Debug, Clone)]
+struct PoolPair {
+    pool: String,
+    i: usize,
+    j: usize,
+}
+
+impl PoolPair {
+    fn parse(ticker: &str) -> Result<Self, Error> {
+        let invalid = || Error::Collector(format!("invalid curve pool ticker: {}", ticker));
+        let mut parts = ticker.split(':');
+        let pool = parts.next().ok_or_else(invalid)?.to_string();
+        let i = parts
+            .next()
+            .and_then(|i| i.parse().ok())
+            .ok_or_else(invalid)?;
+        let j = parts
+            .next()
+            .and_then(|j| j.parse().ok())
+            .ok_or_else(invalid)?;
+        Ok(PoolPair { pool, i, j })
+    }
+}
+
+// curve stable pool collector, price is `get_dy` output for one unit of coin i,
+// volume is pool tvl as a sum of normalized coin balances
+#[derive(Debug, Clone)]
+pub struct CurveMarketDataCollector {
+    schedule: TickerSchedule,
+    request_delay: Duration,
+    max_in_flight: usize,
+    client: EthClient,
+    decimals: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+}
+
+impl CurveMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        Ok(CurveMarketDataCollector {
+            schedule: TickerSchedule::new(&config.tickers, &config.delay)?,
+            request_delay: config.delay.request.into(),
+            max_in_flight: config.max_in_flight.max(1),
+            client: EthClient::new(config.endpoint.clone(), build_client(&config.http)?),
+            decimals: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    // pool coins are immutable, their decimals are requested once,
+    // coins are enumerated until `coins(k)` reverts, nothing is cached on other errors
+    async fn get_pool_decimals(&self, pool: &str) -> Result<Vec<i64>, Error> {
+        if let Some(decimals) = self.decimals.read().await.get(pool) {
+            return Ok(decimals.clone());
+        }
+
+        let mut decimals = vec![];
+        for k in 0..CURVE_MAX_COINS {
+            let coin = match self
+                .client
+                .call(pool, &encode_call(CURVE_COINS_SELECTOR, &[BigInt::from(k)]))
+                .await
+            {
+                Ok(res) => decode_address(&res, 0)?,
+                Err(e) if k > 1 && e.is_permanent() => break,
+                Err(e) => return Err(e),
+            };
+            decimals.push(self.client.decimals(&coin).await?);
+        }
+        self.decimals
+            .write()
+            .await
+            .insert(pool.to_string(), decimals.clone());
+        Ok(decimals)
+    }
+
+    // pair is valid if pool has distinct coins i and j
+    async fn validate_pair(&self, ticker: String) -> Result<(), Error> {
+        let pair = PoolPair::parse(&ticker)?;
+        let coins = self.get_pool_decimals(&pair.pool).await?.len();
+        if pair.i == pair.j || pair.i.max(pair.j) >= coins {
+            return Err(Error::Collector(format!(
+                "curve pool {} has {} coins",
+                pair.pool, coins
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_tvl(&self, pool: &str, decimals: &[i64]) -> Result<BigDecimal, Error> {
+        let mut tvl = BigDecimal::from(0);
+        for (k, coin_decimals) in decimals.iter().enumerate() {
+            let balance = decode_uint(
+                &self
+                    .client
+                    .call(
+                        pool,
+                        &encode_call(CURVE_BALANCES_SELECTOR, &[BigInt::from(k)]),
+                    )
+                    .await?,
+                0,
+            )?;
+            tvl += BigDecimal::new(balance, *coin_decimals);
+        }
+        Ok(tvl)
+    }
+
+    async fn get_market_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let pair = PoolPair::parse(&ticker.ticker)?;
+        let decimals = self.get_pool_decimals(&pair.pool).await?;
+        let (decimals_i, decimals_j) = match (decimals.get(pair.i), decimals.get(pair.j)) {
+            (Some(i), Some(j)) => (*i, *j),
+            _ => {
+                return Err(Error::Collector(format!(
+                    "curve pool {} has no coins {} and {}",
+                    pair.pool, pair.i, pair.j
+                )))
+            }
+        };
+        let dx = BigInt::from(10).pow(decimals_i as u32);
+        let dy = decode_uint(
+            &self
+                .client
+                .call(
+                    &pair.pool,
+                    &encode_call(
+                        CURVE_GET_DY_SELECTOR,
+                        &[BigInt::from(pair.i), BigInt::from(pair.j), dx],
+                    ),
+                )
+                .await?,
+            0,
+        )?;
+        let price = BigDecimal::new(dy, decimals_j);
+
+        Ok(MarketData {
+            provider: CURVE_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker.clone()
+            } else {
+                ticker.alias
+            },
+            price: if ticker.inverted {
+                price.inverse()
+            } else {
+                price
+            },
+            volume: self.get_tvl(&pair.pool, &decimals).await?,
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
+        })
+    }
+}
+
+#[async_trait]
+impl TickerCollector for CurveMarketDataCollector {
+    fn name(&self) -> &'static str {
+        CURVE_PROVIDER_NAME
+    }
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        self.get_market_data(ticker).await
+    }
+}
+
+#[async_trait]
+impl MarketDataCollector for CurveMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let schedule = self.schedule.clone();
+        let (max_in_flight, request_delay) = (self.max_in_flight, self.request_delay);
+        poll_tickers(self, schedule, max_in_flight, request_delay, tx).await
+    }
+
+    async fn validate(&self) -> Result<(), Error> {
+        let rejected = self
+            .schedule
+            .reject(|ticker| self.validate_pair(ticker))
+            .await?;
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Collector(format!(
+                "curve tickers are rejected: {}",
+                rejected.join(", ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PoolPair;
+
+    #[test]
+    fn parse_pool_pair() {
+        let pair = PoolPair::parse("0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7:1:2").unwrap();
+        assert_eq!(pair.pool, "0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7");
+        assert_eq!(pair.i, 1);
+        assert_eq!(pair.j, 2);
+        assert!(PoolPair::parse("0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7:1").is_err());
+        assert!(PoolPair::parse("0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7:a:b").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use super::{MarketData, MarketDataCollector};
+use crate::config::{CollectorConfig, Ticker};
+use crate::error::Error;
+use crate::http::build_client;
+use crate::metrics;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, TimeZone, Utc};
+use pepe_log::error;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{sleep, Instant};
+
+const ECB_PROVIDER_NAME: &str = "ecb";
+const ECB_DAILY_URL: &str = "/stats/eurofxref/eurofxref-daily.xml";
+const ECB_BASE_CURRENCY: &str = "EUR";
+const ECB_CURRENCY_LEN: usize = 3;
+
+// ecb daily reference rates, currency units per one euro
+#[derive(Debug, Clone, PartialEq)]
+struct ReferenceRates {
+    timestamp: i64,
+    rates: HashMap<String, BigDecimal>,
+}
+
+impl ReferenceRates {
+    fn parse(xml: &str) -> Result<Self, Error> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|e| Error::Collector(format!("can't parse ecb rates: {}", e)))?;
+        let mut timestamp = None;
+        let mut rates = HashMap::from([(ECB_BASE_CURRENCY.to_string(), BigDecimal::from(1))]);
+
+        for node in document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "Cube")
+        {
+            if let Some(time) = node.attribute("time") {
+                let date = NaiveDate::from_str(time)
+                    .map_err(|e| Error::Collector(format!("can't parse ecb date: {}", e)))?;
+                timestamp = Some(Utc.from_utc_datetime(&date.into()).timestamp());
+            }
+            if let (Some(currency), Some(rate)) =
+                (node.attribute("currency"), node.attribute("rate"))
+            {
+                rates.insert(
+                    currency.to_string(),
+                    BigDecimal::from_str(rate)
+                        .map_err(|e| Error::Collector(format!("can't parse ecb rate: {}", e)))?,
+                );
+            }
+        }
+
+        Ok(ReferenceRates {
+            timestamp: timestamp
+                .ok_or_else(|| Error::Collector(String::from("no ecb reference date")))?,
+            rates,
+        })
+    }
+
+    // cross rate of `BASEQUOTE` pair
+    fn price(&self, pair: &str) -> Result<BigDecimal, Error> {
+        let rate = |currency: &str| {
+            self.rates
+                .get(currency)
+                .ok_or_else(|| Error::Collector(format!("no ecb rate for {}", currency)))
+        };
+        match (pair.get(..ECB_CURRENCY_LEN), pair.get(ECB_CURRENCY_LEN..)) {
+            (Some(base), Some(quote)) if quote.len() == ECB_CURRENCY_LEN => {
+                Ok(rate(quote)? / rate(base)?)
+            }
+            _ => Err(Error::Collector(format!("invalid fx pair: {}", pair))),
+        }
+    }
+}
+
+// fiat fx collector of ecb daily reference rates,
+// market data has nominal volume and timestamp of the reference date
+#[derive(Debug, Clone)]
+pub struct EcbMarketDataCollector {
+    endpoint: String,
+    tickers: Vec<Ticker>,
+    batch_delay: Duration,
+    client: Client,
+}
+
+impl EcbMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        Ok(EcbMarketDataCollector {
+            endpoint: config.endpoint.clone(),
+            tickers: config.tickers.clone(),
+            batch_delay: config.delay.batch.into(),
+            client: build_client(&config.http)?,
+        })
+    }
+
+    async fn get_rates(&self) -> Result<ReferenceRates, Error> {
+        let url = format!("{}{}", self.endpoint, ECB_DAILY_URL);
+        let res = self.client.get(url).send().await?;
+        if !res.status().is_success() {
+            return Err(Error::Collector(format!(
+                "ecb http status {}",
+                res.status()
+            )));
+        }
+        ReferenceRates::parse(&res.text().await?)
+    }
+
+    fn get_market_data(&self, rates: &ReferenceRates, ticker: Ticker) -> Result<MarketData, Error> {
+        let price = rates.price(&ticker.ticker)?;
+
+        Ok(MarketData {
+            provider: ECB_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker
+            } else {
+                ticker.alias
+            },
+            price: if ticker.inverted {
+                price.inverse()
+            } else {
+                price
+            },
+            volume: BigDecimal::from(1),
+            timestamp: rates.timestamp,
+            book: None,
+            candle: None,
+            latency_ms: None,
+        })
+    }
+}
+
+#[async_trait]
+impl MarketDataCollector for EcbMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        loop {
+            let started = Instant::now();
+            let result = self.get_rates().await;
+            metrics::observe_request(ECB_PROVIDER_NAME, started, &result);
+            match result {
+                Ok(rates) => {
+                    let latency_ms = started.elapsed().as_millis() as u64;
+                    for ticker in &self.tickers {
+                        match self.get_market_data(&rates, ticker.clone()) {
+                            Ok(mut market_data) => {
+                                market_data.latency_ms = Some(latency_ms);
+                                match tx.send(market_data).await {
+                                    Ok(_) => {}
+                                    Err(e) => {
+                                        error!("can't push market from ecb to channel: {}", e)
+                                    }
+                                };
+                            }
+                            Err(e) => {
+                                error!("can't get market from ecb: {}", e);
+                            }
+                        };
+                    }
+                }
+                Err(e) => {
+                    error!("can't get rates from ecb: {}", e);
+                }
+            };
+            sleep(self.batch_delay).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReferenceRates;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    const DAILY_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+	<gesmes:subject>Reference rates</gesmes:subject>
+	<Cube>
+		<Cube time='2022-03-18'>
+			<Cube currency='USD' rate='1.1'/>
+			<Cube currency='GBP' rate='0.8'/>
+		</Cube>
+	</Cube>
+</gesmes:Envelope>"#;
+
+    #[test]
+    fn cross_rates() {
+        let rates = ReferenceRates::parse(DAILY_XML).unwrap();
+        assert_eq!(rates.timestamp, 1647561600);
+        assert_eq!(
+            rates.price("EURUSD").unwrap(),
+            BigDecimal::from_str("1.1").unwrap()
+        );
+        assert_eq!(
+            rates.price("GBPUSD").unwrap(),
+            BigDecimal::from_str("1.375").unwrap()
+        );
+        assert!(rates.price("JPYUSD").is_err());
+        assert!(rates.price("EUR").is_err());
+    }
+}
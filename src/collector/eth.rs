@@ -0,0 +1,151 @@
+use crate::error::Error;
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::ToPrimitive;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const ETH_RPC_NAME: &str = "eth";
+const ETH_RPC_VERSION: &str = "2.0";
+// geth reports reverts with revert data as code 3, without it as -32000
+const ETH_REVERT_ERROR_CODE: i64 = 3;
+const ETH_REVERT_MESSAGE: &str = "execution reverted";
+const ETH_CALL_METHOD: &str = "eth_call";
+const ETH_BLOCK_LATEST: &str = "latest";
+const ERC20_DECIMALS_SELECTOR: &str = "0x313ce567";
+// abi word size in hex chars
+const ETH_WORD_SIZE: usize = 64;
+
+#[derive(Debug, Serialize)]
+struct CallParams<'a> {
+    to: &'a str,
+    data: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Param<'a> {
+    Call(CallParams<'a>),
+    Block(&'a str),
+}
+
+#[derive(Debug, Serialize)]
+struct RpcRequest<'a> {
+    jsonrpc: &'a str,
+    id: u64,
+    method: &'a str,
+    params: Vec<Param<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<String>,
+    error: Option<RpcError>,
+}
+
+// minimal ethereum json-rpc client for read-only contract calls
+#[derive(Debug, Clone)]
+pub struct EthClient {
+    endpoint: String,
+    client: Client,
+}
+
+impl EthClient {
+    pub fn new(endpoint: String, client: Client) -> Self {
+        EthClient { endpoint, client }
+    }
+
+    // eth_call against latest block, returns abi encoded result hex without 0x prefix,
+    // reverted calls fail with permanent error, transport and node errors are transient
+    pub async fn call(&self, to: &str, data: &str) -> Result<String, Error> {
+        let res = self
+            .client
+            .post(self.endpoint.clone())
+            .json(&RpcRequest {
+                jsonrpc: ETH_RPC_VERSION,
+                id: 1,
+                method: ETH_CALL_METHOD,
+                params: vec![
+                    Param::Call(CallParams { to, data }),
+                    Param::Block(ETH_BLOCK_LATEST),
+                ],
+            })
+            .send()
+            .await?;
+        let status = res.status();
+        let res: RpcResponse = res.json().await?;
+
+        match (res.result, res.error) {
+            (_, Some(e)) => Err(rpc_error(status.as_u16(), e)),
+            (Some(result), None) => Ok(result.trim_start_matches("0x").to_string()),
+            (None, None) => Err(Error::Collector(String::from("eth_call empty result"))),
+        }
+    }
+
+    // erc20 token decimals
+    pub async fn decimals(&self, token: &str) -> Result<i64, Error> {
+        decode_uint(&self.call(token, ERC20_DECIMALS_SELECTOR).await?, 0)?
+            .to_i64()
+            .ok_or_else(|| Error::Collector(format!("can't decode {} decimals", token)))
+    }
+}
+
+fn rpc_error(status: u16, e: RpcError) -> Error {
+    Error::Exchange {
+        exchange: ETH_RPC_NAME.to_string(),
+        status,
+        code: e.code,
+        permanent: e.code == ETH_REVERT_ERROR_CODE || e.message.contains(ETH_REVERT_MESSAGE),
+        message: e.message,
+    }
+}
+
+// abi encode call data of selector with uint256 arguments
+pub fn encode_call(selector: &str, args: &[BigInt]) -> String {
+    args.iter().fold(selector.to_string(), |data, arg| {
+        format!("{}{:0>64}", data, arg.to_str_radix(16))
+    })
+}
+
+// decode uint256 word by index from abi encoded result
+pub fn decode_uint(result: &str, index: usize) -> Result<BigInt, Error> {
+    result
+        .get(index * ETH_WORD_SIZE..(index + 1) * ETH_WORD_SIZE)
+        .and_then(|word| BigInt::parse_bytes(word.as_bytes(), 16))
+        .ok_or_else(|| Error::Collector(format!("can't decode abi word {}", index)))
+}
+
+// decode address word by index from abi encoded result
+pub fn decode_address(result: &str, index: usize) -> Result<String, Error> {
+    result
+        .get(index * ETH_WORD_SIZE + 24..(index + 1) * ETH_WORD_SIZE)
+        .map(|address| format!("0x{}", address))
+        .ok_or_else(|| Error::Collector(format!("can't decode abi address {}", index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rpc_error, RpcError};
+
+    #[test]
+    fn revert_error() {
+        let error = |code: i64, message: &str| {
+            rpc_error(
+                200,
+                RpcError {
+                    code,
+                    message: message.to_string(),
+                },
+            )
+        };
+        assert!(error(3, "execution reverted: invalid coin").is_permanent());
+        assert!(error(-32000, "execution reverted").is_permanent());
+        assert!(!error(-32005, "daily request count exceeded").is_permanent());
+        assert!(!error(-32000, "header not found").is_permanent());
+    }
+}
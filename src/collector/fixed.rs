@@ -0,0 +1,187 @@
+use super::{MarketData, MarketDataCollector};
+use crate::config::{CollectorConfig, ScheduledPrice, Ticker};
+use crate::error::Error;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::Utc;
+use pepe_log::error;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{sleep, Instant};
+
+const STATIC_PROVIDER_NAME: &str = "static";
+
+// static collector emits configured prices, optionally changed by schedule
+// or random walk, for pinned tickers and test environments,
+// its prices override other sources of the ticker in aggregation
+#[derive(Debug, Clone)]
+pub struct StaticMarketDataCollector {
+    // tickers with initial prices
+    tickers: Vec<(Ticker, BigDecimal)>,
+    batch_delay: Duration,
+}
+
+impl StaticMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        let zero = BigDecimal::from(0);
+        let tickers = config
+            .tickers
+            .iter()
+            .map(|ticker| {
+                let invalid = |reason: &str| {
+                    Err(Error::Collector(format!(
+                        "invalid static ticker {}: {}",
+                        ticker.ticker, reason
+                    )))
+                };
+                let price = match &ticker.price {
+                    Some(price) if *price > zero => price.clone(),
+                    Some(_) => return invalid("price must be positive"),
+                    None => return invalid("no price"),
+                };
+                if ticker
+                    .schedule
+                    .iter()
+                    .any(|scheduled| scheduled.price <= zero)
+                {
+                    return invalid("scheduled price must be positive");
+                }
+                match &ticker.walk {
+                    Some(walk) if *walk < zero || *walk >= BigDecimal::from(1) => {
+                        invalid("walk must be in [0, 1)")
+                    }
+                    _ => Ok((ticker.clone(), price)),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(StaticMarketDataCollector {
+            tickers,
+            batch_delay: config.delay.batch.into(),
+        })
+    }
+
+    fn get_market_data(&self, ticker: &Ticker, price: BigDecimal) -> MarketData {
+        MarketData {
+            provider: STATIC_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker.clone()
+            } else {
+                ticker.alias.clone()
+            },
+            price: if ticker.inverted {
+                price.inverse()
+            } else {
+                price
+            },
+            volume: ticker.volume.clone().unwrap_or_else(|| BigDecimal::from(1)),
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
+        }
+    }
+}
+
+// price at elapsed time: the last due scheduled price or the initial one
+fn scheduled_price(
+    initial: &BigDecimal,
+    schedule: &[ScheduledPrice],
+    elapsed: Duration,
+) -> BigDecimal {
+    schedule
+        .iter()
+        .filter(|scheduled| {
+            let after: Duration = scheduled.after.into();
+            after <= elapsed
+        })
+        .max_by_key(|scheduled| -> Duration { scheduled.after.into() })
+        .map(|scheduled| scheduled.price.clone())
+        .unwrap_or_else(|| initial.clone())
+}
+
+// price moved by uniform random relative step in [-walk, walk],
+// walk is below 1, so positive price stays positive
+fn random_step(price: &BigDecimal, walk: &BigDecimal) -> BigDecimal {
+    let step = BigDecimal::from_f64(rand::thread_rng().gen_range(-1.0..=1.0)).unwrap_or_default();
+    price * (BigDecimal::from(1) + step * walk)
+}
+
+#[async_trait]
+impl MarketDataCollector for StaticMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let started = Instant::now();
+        // scheduled base and walked price of tickers
+        let mut prices: HashMap<String, (BigDecimal, BigDecimal)> = HashMap::new();
+
+        loop {
+            for (ticker, initial) in &self.tickers {
+                let base = scheduled_price(initial, &ticker.schedule, started.elapsed());
+                // random walk is restarted on every scheduled price change
+                let price = match (prices.get(&ticker.ticker), &ticker.walk) {
+                    (Some((prev_base, prev_price)), Some(walk)) if *prev_base == base => {
+                        random_step(prev_price, walk)
+                    }
+                    (_, Some(walk)) => random_step(&base, walk),
+                    (_, None) => base.clone(),
+                };
+                prices.insert(ticker.ticker.clone(), (base, price.clone()));
+
+                if let Err(e) = tx.send(self.get_market_data(ticker, price)).await {
+                    error!("can't push market from static to channel: {}", e);
+                }
+            }
+            sleep(self.batch_delay).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_step, scheduled_price};
+    use crate::config::ScheduledPrice;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    #[test]
+    fn schedule() {
+        let initial = BigDecimal::from(1);
+        let schedule = vec![
+            ScheduledPrice {
+                after: Duration::from_secs(120).into(),
+                price: BigDecimal::from_str("0.9").unwrap(),
+            },
+            ScheduledPrice {
+                after: Duration::from_secs(60).into(),
+                price: BigDecimal::from_str("0.95").unwrap(),
+            },
+        ];
+
+        assert_eq!(
+            scheduled_price(&initial, &schedule, Duration::from_secs(0)),
+            BigDecimal::from(1)
+        );
+        assert_eq!(
+            scheduled_price(&initial, &schedule, Duration::from_secs(90)),
+            BigDecimal::from_str("0.95").unwrap()
+        );
+        assert_eq!(
+            scheduled_price(&initial, &schedule, Duration::from_secs(600)),
+            BigDecimal::from_str("0.9").unwrap()
+        );
+    }
+
+    #[test]
+    fn walk() {
+        let price = BigDecimal::from(100);
+        let walk = BigDecimal::from_str("0.01").unwrap();
+        for _ in 0..100 {
+            let next = random_step(&price, &walk);
+            assert!(next >= BigDecimal::from(99) && next <= BigDecimal::from(101));
+        }
+    }
+}
@@ -1,21 +1,160 @@
-use crate::{config::CollectorConfig, error::Error};
+use self::schedule::TickerSchedule;
+use crate::config::{CollectorConfig, Ticker};
+use crate::error::Error;
+use crate::metrics;
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
-use serde::Serialize;
+use pepe_log::error;
+use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
-use std::sync::Arc;
-use tokio::sync::mpsc::Sender;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc::Sender, Semaphore};
+use tokio::time::{sleep, Instant};
 
 mod binance;
 mod bitfinex;
+mod curve;
+mod ecb;
+mod eth;
+mod fixed;
+mod replay;
+mod schedule;
+mod uniswap;
+mod waves;
 
-#[derive(Debug, Clone, Serialize, SlogValue, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, SlogValue, PartialEq)]
 pub struct MarketData {
     pub provider: String,
     pub ticker: String,
     pub price: BigDecimal,
     pub volume: BigDecimal,
     pub timestamp: i64,
+    #[serde(default)]
+    pub book: Option<Book>,
+    #[serde(default)]
+    pub candle: Option<Candle>,
+    // collector response latency
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Book {
+    pub bid: BigDecimal,
+    pub ask: BigDecimal,
+    pub spread: BigDecimal,
+}
+
+impl Book {
+    // top of book, for inverted tickers bid and ask are reciprocal and swapped,
+    // empty (non positive) and crossed books are rejected
+    pub fn new(bid: BigDecimal, ask: BigDecimal, inverted: bool) -> Result<Self, Error> {
+        let zero = BigDecimal::from(0);
+        if bid <= zero || ask <= zero || ask < bid {
+            return Err(Error::Collector(format!(
+                "invalid book: bid {}, ask {}",
+                bid, ask
+            )));
+        }
+        let (bid, ask) = if inverted {
+            (ask.inverse(), bid.inverse())
+        } else {
+            (bid, ask)
+        };
+        Ok(Book {
+            spread: &ask - &bid,
+            bid,
+            ask,
+        })
+    }
+
+    pub fn mid(&self) -> BigDecimal {
+        (&self.bid + &self.ask) / BigDecimal::from(2)
+    }
+
+    // fails if relative spread exceeds max spread
+    pub fn check_spread(&self, max_spread: &Option<BigDecimal>) -> Result<(), Error> {
+        match max_spread {
+            Some(max_spread) if &self.spread / self.mid() > *max_spread => Err(Error::Collector(
+                format!("spread {} is too wide", self.spread),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Candle {
+    pub open: BigDecimal,
+    pub high: BigDecimal,
+    pub low: BigDecimal,
+    pub close: BigDecimal,
+}
+
+impl Candle {
+    // ohlc, for inverted tickers prices are reciprocal and high/low swapped
+    pub fn new(
+        open: BigDecimal,
+        high: BigDecimal,
+        low: BigDecimal,
+        close: BigDecimal,
+        inverted: bool,
+    ) -> Self {
+        if inverted {
+            Candle {
+                open: open.inverse(),
+                high: low.inverse(),
+                low: high.inverse(),
+                close: close.inverse(),
+            }
+        } else {
+            Candle {
+                open,
+                high,
+                low,
+                close,
+            }
+        }
+    }
+
+    pub fn weighted(&self, weight: &BigDecimal) -> Self {
+        Candle {
+            open: &self.open * weight,
+            high: &self.high * weight,
+            low: &self.low * weight,
+            close: &self.close * weight,
+        }
+    }
+
+    pub fn add(&self, other: &Candle) -> Self {
+        Candle {
+            open: &self.open + &other.open,
+            high: &self.high + &other.high,
+            low: &self.low + &other.low,
+            close: &self.close + &other.close,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub price: BigDecimal,
+    pub amount: BigDecimal,
+}
+
+// volume-weighted average price and quote volume of trades
+pub fn vwap(trades: &[Trade]) -> Result<(BigDecimal, BigDecimal), Error> {
+    let amount: BigDecimal = trades.iter().map(|trade| trade.amount.clone()).sum();
+    let volume: BigDecimal = trades
+        .iter()
+        .map(|trade| &trade.price * &trade.amount)
+        .sum();
+    if amount == BigDecimal::from(0) {
+        return Err(Error::Collector(String::from("no trades in window")));
+    }
+    Ok((&volume / amount, volume))
 }
 
 #[derive(Debug, Clone, Serialize, SlogValue)]
@@ -25,7 +164,119 @@ pub struct MarketDataVec {
 
 #[async_trait]
 pub trait MarketDataCollector: Send + Sync {
-    async fn collect(&self, tx: Sender<MarketData>);
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>);
+
+    // checks configured tickers against venue metadata and excludes not trading ones
+    async fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// collector requesting market data ticker by ticker
+#[async_trait]
+pub trait TickerCollector: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error>;
+}
+
+// polls scheduled tickers with at most `max_in_flight` concurrent requests,
+// ticker is skipped while its previous request is in flight, so its market data
+// is never reordered, tickers failing with permanent errors are excluded
+pub async fn poll_tickers<C: TickerCollector>(
+    collector: Arc<C>,
+    mut schedule: TickerSchedule,
+    max_in_flight: usize,
+    request_delay: Duration,
+    tx: Sender<MarketData>,
+) {
+    let name = collector.name();
+    let in_flight = Arc::new(Semaphore::new(max_in_flight.max(1)));
+    let pending = Arc::new(Mutex::new(HashSet::new()));
+    let excluded = schedule.excluded();
+    while let Some(ticker) = schedule.next().await {
+        let ticker_name = ticker.ticker.clone();
+        if !set_pending(&pending, &ticker_name, true) {
+            continue;
+        }
+        let permit = match in_flight.clone().acquire_owned().await {
+            Ok(permit) => permit,
+            Err(e) => {
+                error!("can't schedule {} request: {}", name, e);
+                break;
+            }
+        };
+        let collector = collector.clone();
+        let tx = tx.clone();
+        let excluded = excluded.clone();
+        let pending = pending.clone();
+
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let result = collector.fetch_ticker(ticker).await;
+            metrics::observe_request(name, started, &result);
+            match result {
+                Ok(mut market_data) => {
+                    market_data.latency_ms = Some(started.elapsed().as_millis() as u64);
+                    if let Err(e) = tx.send(market_data).await {
+                        error!("can't push market from {} to channel: {}", name, e);
+                    }
+                }
+                Err(e) if e.is_permanent() => {
+                    if excluded.exclude(&ticker_name) {
+                        error!("ticker {} excluded from {}: {}", ticker_name, name, e);
+                    }
+                }
+                Err(e) => {
+                    error!("can't get market from {}: {}", name, e);
+                }
+            };
+            set_pending(&pending, &ticker_name, false);
+            drop(permit);
+        });
+
+        sleep(request_delay).await
+    }
+}
+
+// marks ticker request as pending or completed, returns false if already pending
+fn set_pending(pending: &Mutex<HashSet<String>>, ticker: &str, is_pending: bool) -> bool {
+    match pending.lock() {
+        Ok(mut pending) if is_pending => pending.insert(ticker.to_string()),
+        Ok(mut pending) => pending.remove(ticker),
+        Err(_) => false,
+    }
+}
+
+// replayed records keep provider names they were recorded with
+const REPLAY_COLLECTOR_KIND: &str = "replay";
+
+// market data ttl overrides keyed by provider name collectors emit, that is collector
+// kind, so collectors of the same kind must agree on ttl, replay ttl can't apply
+pub fn provider_ttls(config: &[CollectorConfig]) -> Result<HashMap<String, Duration>, Error> {
+    let mut ttls = HashMap::new();
+    for collector_config in config.iter().filter(|config| config.enabled) {
+        let ttl: Duration = match collector_config.ttl {
+            Some(ttl) => ttl.into(),
+            None => continue,
+        };
+        if collector_config.kind == REPLAY_COLLECTOR_KIND {
+            return Err(Error::Collector(
+                "ttl of replay collector can't apply, replayed data keeps its providers"
+                    .to_string(),
+            ));
+        }
+        match ttls.insert(collector_config.kind.clone(), ttl) {
+            Some(other) if other != ttl => {
+                return Err(Error::Collector(format!(
+                    "{} collectors have different ttl",
+                    collector_config.kind
+                )))
+            }
+            _ => {}
+        }
+    }
+    Ok(ttls)
 }
 
 pub fn init_collectors(
@@ -39,13 +290,208 @@ pub fn init_collectors(
                 match collector_config.kind.as_str() {
                     "binance" => Ok(Arc::new(binance::BinanceMarketDataCollector::new(
                         collector_config,
-                    ))),
+                    )?)),
                     "bitfinex" => Ok(Arc::new(bitfinex::BitfinexMarketDataCollector::new(
                         collector_config,
-                    ))),
+                    )?)),
+                    "curve" => Ok(Arc::new(curve::CurveMarketDataCollector::new(
+                        collector_config,
+                    )?)),
+                    "ecb" => Ok(Arc::new(ecb::EcbMarketDataCollector::new(
+                        collector_config,
+                    )?)),
+                    "replay" => Ok(Arc::new(replay::ReplayMarketDataCollector::new(
+                        collector_config,
+                    )?)),
+                    "static" => Ok(Arc::new(fixed::StaticMarketDataCollector::new(
+                        collector_config,
+                    )?)),
+                    "uniswap" => Ok(Arc::new(uniswap::UniswapMarketDataCollector::new(
+                        collector_config,
+                    )?)),
+                    "waves" => Ok(Arc::new(waves::WavesMarketDataCollector::new(
+                        collector_config,
+                    )?)),
                     _ => Err(Error::Collector(String::from("unsupported kind"))),
                 }
             },
         )
         .collect::<Result<Vec<Arc<dyn MarketDataCollector>>, Error>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::schedule::TickerSchedule;
+    use super::{poll_tickers, provider_ttls, vwap, Book, MarketData, TickerCollector, Trade};
+    use crate::config::{CollectorConfig, DelayConfig, Ticker};
+    use crate::error::Error;
+    use crate::fixtures::market_data;
+    use async_trait::async_trait;
+    use bigdecimal::BigDecimal;
+    use config::{Config, File, FileFormat};
+    use std::collections::{HashMap, HashSet};
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time::{sleep, timeout};
+
+    #[derive(Default)]
+    struct SlowCollector {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        tickers: Mutex<HashSet<String>>,
+        overlaps: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TickerCollector for SlowCollector {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            if !self.tickers.lock().unwrap().insert(ticker.ticker.clone()) {
+                self.overlaps.fetch_add(1, Ordering::SeqCst);
+            }
+            sleep(Duration::from_millis(40)).await;
+            self.tickers.lock().unwrap().remove(&ticker.ticker);
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(market_data(
+                "slow",
+                &ticker.ticker,
+                BigDecimal::from(1),
+                BigDecimal::from(1),
+                0,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_concurrency() {
+        let delay = DelayConfig {
+            request: Duration::from_millis(0).into(),
+            batch: Duration::from_millis(5).into(),
+            classes: HashMap::new(),
+        };
+        let tickers = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| Ticker {
+                ticker: name.to_string(),
+                alias: "".to_string(),
+                inverted: false,
+                interval: None,
+                class: None,
+                price: None,
+                volume: None,
+                walk: None,
+                schedule: vec![],
+            })
+            .collect::<Vec<_>>();
+        let schedule = TickerSchedule::new(&tickers, &delay).unwrap();
+        let collector = Arc::new(SlowCollector::default());
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let polling = poll_tickers(collector.clone(), schedule, 2, Duration::ZERO, tx);
+        let _ = timeout(Duration::from_millis(300), polling).await;
+
+        assert_eq!(collector.max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(collector.overlaps.load(Ordering::SeqCst), 0);
+        assert!(rx.try_recv().unwrap().latency_ms.is_some());
+    }
+
+    #[test]
+    fn book_inverted() {
+        let book = Book::new(
+            BigDecimal::from_str("4").unwrap(),
+            BigDecimal::from_str("5").unwrap(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(book.bid, BigDecimal::from_str("0.2").unwrap());
+        assert_eq!(book.ask, BigDecimal::from_str("0.25").unwrap());
+        assert_eq!(book.spread, BigDecimal::from_str("0.05").unwrap());
+        assert_eq!(book.mid(), BigDecimal::from_str("0.225").unwrap());
+    }
+
+    #[test]
+    fn book_spread() {
+        let book = Book::new(
+            BigDecimal::from_str("99").unwrap(),
+            BigDecimal::from_str("101").unwrap(),
+            false,
+        )
+        .unwrap();
+        assert!(book.check_spread(&None).is_ok());
+        assert!(book
+            .check_spread(&Some(BigDecimal::from_str("0.05").unwrap()))
+            .is_ok());
+        assert!(book
+            .check_spread(&Some(BigDecimal::from_str("0.01").unwrap()))
+            .is_err());
+    }
+
+    #[test]
+    fn book_invalid() {
+        let book = |bid: &str, ask: &str, inverted: bool| {
+            Book::new(
+                BigDecimal::from_str(bid).unwrap(),
+                BigDecimal::from_str(ask).unwrap(),
+                inverted,
+            )
+        };
+        assert!(book("0", "5", false).is_err());
+        assert!(book("0", "0", false).is_err());
+        assert!(book("-1", "5", false).is_err());
+        assert!(book("4", "0", true).is_err());
+        // crossed
+        assert!(book("5", "4", false).is_err());
+        assert!(book("5", "4", true).is_err());
+        assert!(book("4", "4", false).is_ok());
+    }
+
+    #[test]
+    fn trades_vwap() {
+        assert!(vwap(&[]).is_err());
+
+        let trades = [
+            Trade {
+                price: BigDecimal::from_str("100").unwrap(),
+                amount: BigDecimal::from_str("1").unwrap(),
+            },
+            Trade {
+                price: BigDecimal::from_str("110").unwrap(),
+                amount: BigDecimal::from_str("3").unwrap(),
+            },
+        ];
+        let (price, volume) = vwap(&trades).unwrap();
+        assert_eq!(price, BigDecimal::from_str("107.5").unwrap());
+        assert_eq!(volume, BigDecimal::from_str("430").unwrap());
+    }
+
+    #[test]
+    fn ttls() {
+        let collector = |kind: &str, ttl: &str| -> CollectorConfig {
+            Config::builder()
+                .add_source(File::from_str(
+                    &format!(
+                        "kind: {}\nenabled: true\nendpoint: \"\"\nttl: {}\ndelay:\n  batch: 5s\n  request: 100ms\ntickers: []",
+                        kind, ttl
+                    ),
+                    FileFormat::Yaml,
+                ))
+                .build()
+                .unwrap()
+                .try_deserialize()
+                .unwrap()
+        };
+
+        let ttls = provider_ttls(&[collector("ecb", "96h"), collector("ecb", "96h")]).unwrap();
+        assert_eq!(ttls["ecb"], Duration::from_secs(96 * 3600));
+        assert!(provider_ttls(&[collector("ecb", "96h"), collector("ecb", "1m")]).is_err());
+        assert!(provider_ttls(&[collector("replay", "1m")]).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use super::{MarketData, MarketDataCollector};
+use crate::config::CollectorConfig;
+use crate::error::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use pepe_log::{error, info};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{sleep_until, Instant};
+
+const REPLAY_CSV_EXTENSION: &str = "csv";
+const REPLAY_CSV_COLUMNS: [&str; 5] = ["provider", "ticker", "price", "volume", "timestamp"];
+
+// replays recorded market data file (jsonl or csv) keeping original intervals
+// divided by speed, timestamps are shifted to replay start
+#[derive(Debug, Clone)]
+pub struct ReplayMarketDataCollector {
+    records: Vec<MarketData>,
+    speed: f64,
+}
+
+impl ReplayMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        if config.speed <= 0.0 {
+            return Err(Error::Collector(String::from(
+                "replay speed must be positive",
+            )));
+        }
+
+        let path = Path::new(&config.endpoint);
+        let content = fs::read_to_string(path)?;
+        let mut records = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(REPLAY_CSV_EXTENSION) => parse_csv(&content)?,
+            _ => parse_jsonl(&content)?,
+        };
+
+        let tickers = config
+            .tickers
+            .iter()
+            .map(|ticker| ticker.ticker.clone())
+            .collect::<HashSet<_>>();
+        if !tickers.is_empty() {
+            records.retain(|record| tickers.contains(&record.ticker));
+        }
+        records.sort_by_key(|record| record.timestamp);
+
+        Ok(ReplayMarketDataCollector {
+            records,
+            speed: config.speed,
+        })
+    }
+}
+
+fn parse_jsonl(content: &str) -> Result<Vec<MarketData>, Error> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+// csv with header of provider,ticker,price,volume,timestamp columns in any order
+fn parse_csv(content: &str) -> Result<Vec<MarketData>, Error> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| Error::Collector(String::from("empty replay csv")))?
+        .split(',')
+        .map(|column| column.trim())
+        .collect::<Vec<_>>();
+    let index = REPLAY_CSV_COLUMNS
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| Error::Collector(format!("no {} column in replay csv", name)))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    lines
+        .map(|line| {
+            let values = line
+                .split(',')
+                .map(|value| value.trim())
+                .collect::<Vec<_>>();
+            let value = |column: usize| {
+                values
+                    .get(index[column])
+                    .copied()
+                    .ok_or_else(|| Error::Collector(format!("invalid replay csv line: {}", line)))
+            };
+            let decimal = |column: usize| {
+                BigDecimal::from_str(value(column)?)
+                    .map_err(|e| Error::Collector(format!("invalid replay csv decimal: {}", e)))
+            };
+
+            Ok(MarketData {
+                provider: value(0)?.to_string(),
+                ticker: value(1)?.to_string(),
+                price: decimal(2)?,
+                volume: decimal(3)?,
+                timestamp: value(4)?.parse().map_err(|e| {
+                    Error::Collector(format!("invalid replay csv timestamp: {}", e))
+                })?,
+                book: None,
+                candle: None,
+                latency_ms: None,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl MarketDataCollector for ReplayMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let first_timestamp = match self.records.first() {
+            Some(record) => record.timestamp,
+            None => return,
+        };
+        let start = Instant::now();
+        let start_timestamp = Utc::now().timestamp();
+
+        for record in &self.records {
+            let offset = Duration::from_secs((record.timestamp - first_timestamp) as u64)
+                .div_f64(self.speed);
+            sleep_until(start + offset).await;
+
+            let mut market_data = record.clone();
+            market_data.timestamp = start_timestamp + offset.as_secs() as i64;
+            if let Err(e) = tx.send(market_data).await {
+                error!("can't push market from replay to channel: {}", e);
+            }
+        }
+        info!("replay finished"; "records" => self.records.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_csv, parse_jsonl};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_records() {
+        let jsonl = parse_jsonl(
+            r#"{"provider":"binance","ticker":"BTC","price":"42580.02","volume":"18555.7","timestamp":1647561600}
+
+{"provider":"bitfinex","ticker":"BTC","price":"42562","volume":"1929.4","timestamp":1647561605,"book":null}"#,
+        )
+        .unwrap();
+        let csv = parse_csv(
+            "timestamp,provider,ticker,price,volume
+1647561600,binance,BTC,42580.02,18555.7
+1647561605,bitfinex,BTC,42562,1929.4",
+        )
+        .unwrap();
+
+        assert_eq!(jsonl, csv);
+        assert_eq!(csv.len(), 2);
+        assert_eq!(csv[0].price, BigDecimal::from_str("42580.02").unwrap());
+        assert_eq!(csv[1].timestamp, 1647561605);
+        assert!(parse_csv("provider,ticker\nbinance,BTC").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::config::{DelayConfig, Ticker};
+use crate::error::Error;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::{sleep, sleep_until, Instant};
+
+#[derive(Debug, Default)]
+struct Exclusions {
+    // not listed or halted on venue, included back once trading
+    not_trading: HashSet<String>,
+    // failed with permanent errors, never included back
+    failing: HashSet<String>,
+}
+
+// tickers excluded from polling, shared by schedule and its requests
+#[derive(Debug, Clone, Default)]
+pub struct ExcludedTickers(Arc<RwLock<Exclusions>>);
+
+impl ExcludedTickers {
+    // excludes permanently failing ticker, returns true if it was not failing yet
+    pub fn exclude(&self, ticker: &str) -> bool {
+        match self.0.write() {
+            Ok(mut excluded) => excluded.failing.insert(ticker.to_string()),
+            Err(_) => false,
+        }
+    }
+
+    pub fn set_trading(&self, ticker: &str, trading: bool) {
+        if let Ok(mut excluded) = self.0.write() {
+            if trading {
+                excluded.not_trading.remove(ticker);
+            } else {
+                excluded.not_trading.insert(ticker.to_string());
+            }
+        }
+    }
+
+    pub fn contains(&self, ticker: &str) -> bool {
+        match self.0.read() {
+            Ok(excluded) => {
+                excluded.not_trading.contains(ticker) || excluded.failing.contains(ticker)
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledTicker {
+    ticker: Ticker,
+    interval: Duration,
+    due: Instant,
+}
+
+// polling schedule of collector tickers, each ticker is polled with its own
+// interval (ticker override, priority class or batch delay), due tickers with
+// shorter interval go first
+#[derive(Debug, Clone)]
+pub struct TickerSchedule {
+    tickers: Vec<ScheduledTicker>,
+    excluded: ExcludedTickers,
+}
+
+impl TickerSchedule {
+    pub fn new(tickers: &[Ticker], delay: &DelayConfig) -> Result<Self, Error> {
+        let now = Instant::now();
+        Ok(TickerSchedule {
+            tickers: tickers
+                .iter()
+                .map(|ticker| {
+                    Ok(ScheduledTicker {
+                        ticker: ticker.clone(),
+                        interval: ticker_interval(ticker, delay)?,
+                        due: now,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+            excluded: ExcludedTickers::default(),
+        })
+    }
+
+    pub fn excluded(&self) -> ExcludedTickers {
+        self.excluded.clone()
+    }
+
+    // excludes tickers rejected by `check`, returns them with rejection reasons,
+    // permanent and collector errors reject a ticker, other errors fail validation
+    pub async fn reject<F, Fut>(&self, check: F) -> Result<Vec<String>, Error>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let mut rejected = vec![];
+        for scheduled in &self.tickers {
+            let ticker = &scheduled.ticker.ticker;
+            match check(ticker.clone()).await {
+                Ok(()) => {}
+                Err(e) if e.is_permanent() || matches!(e, Error::Collector(_)) => {
+                    self.excluded.exclude(ticker);
+                    rejected.push(format!("{} ({})", ticker, e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(rejected)
+    }
+
+    // excludes tickers missing in venue trading symbols and includes back
+    // the listed ones unless they fail permanently, returns not trading tickers
+    pub fn update_trading(&self, trading: &HashSet<String>) -> Vec<String> {
+        let mut not_trading = vec![];
+        for scheduled in &self.tickers {
+            let ticker = &scheduled.ticker.ticker;
+            let is_trading = trading.contains(ticker);
+            self.excluded.set_trading(ticker, is_trading);
+            if !is_trading {
+                not_trading.push(ticker.clone());
+            }
+        }
+        not_trading
+    }
+
+    // waits for the next due not excluded ticker and reschedules it
+    pub async fn next(&mut self) -> Option<Ticker> {
+        let min_interval = self
+            .tickers
+            .iter()
+            .map(|scheduled| scheduled.interval)
+            .min()?;
+        loop {
+            let excluded = &self.excluded;
+            match self
+                .tickers
+                .iter_mut()
+                .filter(|scheduled| !excluded.contains(&scheduled.ticker.ticker))
+                .min_by_key(|scheduled| (scheduled.due, scheduled.interval))
+            {
+                Some(scheduled) => {
+                    sleep_until(scheduled.due).await;
+                    scheduled.due = Instant::now() + scheduled.interval;
+                    return Some(scheduled.ticker.clone());
+                }
+                None => sleep(min_interval).await,
+            }
+        }
+    }
+}
+
+fn ticker_interval(ticker: &Ticker, delay: &DelayConfig) -> Result<Duration, Error> {
+    match (&ticker.interval, &ticker.class) {
+        (Some(interval), _) => Ok((*interval).into()),
+        (None, Some(class)) => delay
+            .classes
+            .get(class)
+            .map(|interval| (*interval).into())
+            .ok_or_else(|| {
+                Error::Collector(format!(
+                    "unknown priority class {} of {}",
+                    class, ticker.ticker
+                ))
+            }),
+        (None, None) => Ok(delay.batch.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TickerSchedule;
+    use crate::config::{DelayConfig, Ticker};
+    use crate::error::Error;
+    use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
+
+    fn ticker(name: &str, interval: Option<Duration>, class: Option<&str>) -> Ticker {
+        Ticker {
+            ticker: name.to_string(),
+            alias: "".to_string(),
+            inverted: false,
+            interval: interval.map(|interval| interval.into()),
+            class: class.map(|class| class.to_string()),
+            price: None,
+            volume: None,
+            walk: None,
+            schedule: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn priorities() {
+        let delay = DelayConfig {
+            request: Duration::from_millis(0).into(),
+            batch: Duration::from_secs(60).into(),
+            classes: HashMap::from([("major".to_string(), Duration::from_millis(30).into())]),
+        };
+        let tickers = [
+            ticker("DOGEUSDT", None, None),
+            ticker("BTCUSDT", None, Some("major")),
+            ticker("ETHUSDT", Some(Duration::from_millis(50)), None),
+        ];
+        let mut schedule = TickerSchedule::new(&tickers, &delay).unwrap();
+
+        let mut polled = vec![];
+        for _ in 0..5 {
+            polled.push(schedule.next().await.unwrap().ticker);
+        }
+        assert_eq!(
+            polled,
+            ["BTCUSDT", "ETHUSDT", "DOGEUSDT", "BTCUSDT", "ETHUSDT"]
+        );
+
+        schedule.excluded().exclude("BTCUSDT");
+        assert_eq!(schedule.next().await.unwrap().ticker, "ETHUSDT");
+
+        // permanently failing ticker stays excluded even if it's trading
+        let trading = HashSet::from(["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
+        assert_eq!(schedule.update_trading(&trading), vec!["DOGEUSDT"]);
+        assert!(schedule.excluded().contains("BTCUSDT"));
+        assert!(schedule.excluded().contains("DOGEUSDT"));
+        assert!(!schedule.excluded().contains("ETHUSDT"));
+
+        let trading = HashSet::from(["DOGEUSDT".to_string(), "ETHUSDT".to_string()]);
+        assert_eq!(schedule.update_trading(&trading), vec!["BTCUSDT"]);
+        assert!(!schedule.excluded().contains("DOGEUSDT"));
+        assert!(schedule.excluded().contains("BTCUSDT"));
+
+        assert!(TickerSchedule::new(&[ticker("BTCUSDT", None, Some("unknown"))], &delay).is_err());
+    }
+
+    #[tokio::test]
+    async fn reject() {
+        let delay = DelayConfig {
+            request: Duration::from_millis(0).into(),
+            batch: Duration::from_secs(60).into(),
+            classes: HashMap::new(),
+        };
+        let tickers = [
+            ticker("POOL", None, None),
+            ticker("REVERT", None, None),
+            ticker("MALFORMED", None, None),
+        ];
+        let schedule = TickerSchedule::new(&tickers, &delay).unwrap();
+        let check = |ticker: String| async move {
+            match ticker.as_str() {
+                "REVERT" => Err(Error::Exchange {
+                    exchange: "eth".to_string(),
+                    status: 200,
+                    code: 3,
+                    message: "execution reverted".to_string(),
+                    permanent: true,
+                }),
+                "MALFORMED" => Err(Error::Collector("invalid ticker".to_string())),
+                _ => Ok(()),
+            }
+        };
+        assert_eq!(schedule.reject(check).await.unwrap().len(), 2);
+        assert!(!schedule.excluded().contains("POOL"));
+        assert!(schedule.excluded().contains("REVERT"));
+        assert!(schedule.excluded().contains("MALFORMED"));
+
+        let unavailable = |_| async { Err(Error::HttpServer("node is down".to_string())) };
+        assert!(schedule.reject(unavailable).await.is_err());
+    }
+}
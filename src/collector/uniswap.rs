@@ -0,0 +1,271 @@
+use super::eth::{decode_address, decode_uint, encode_call, EthClient};
+use super::schedule::TickerSchedule;
+use super::{poll_tickers, MarketData, MarketDataCollector, TickerCollector};
+use crate::config::{CollectorConfig, Ticker};
+use crate::error::Error;
+use crate::http::build_client;
+use async_trait::async_trait;
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc::Sender, RwLock};
+
+const UNISWAP_PROVIDER_NAME: &str = "uniswap";
+const UNISWAP_SLOT0_SELECTOR: &str = "0x3850c7bd";
+const UNISWAP_LIQUIDITY_SELECTOR: &str = "0x1a686502";
+const UNISWAP_TOKEN0_SELECTOR: &str = "0x0dfe1681";
+const UNISWAP_TOKEN1_SELECTOR: &str = "0xd21220a7";
+const Q96_BITS: usize = 96;
+
+// decimals of pool tokens
+#[derive(Debug, Clone, Copy)]
+struct PoolDecimals {
+    token0: i64,
+    token1: i64,
+}
+
+// uniswap v3 pool collector, ticker is a pool address,
+// price is token0 price in token1
+#[derive(Debug, Clone)]
+pub struct UniswapMarketDataCollector {
+    schedule: TickerSchedule,
+    request_delay: Duration,
+    max_in_flight: usize,
+    client: EthClient,
+    decimals: Arc<RwLock<HashMap<String, PoolDecimals>>>,
+}
+
+impl UniswapMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        Ok(UniswapMarketDataCollector {
+            schedule: TickerSchedule::new(&config.tickers, &config.delay)?,
+            request_delay: config.delay.request.into(),
+            max_in_flight: config.max_in_flight.max(1),
+            client: EthClient::new(config.endpoint.clone(), build_client(&config.http)?),
+            decimals: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    async fn get_token_decimals(&self, pool: &str, selector: &str) -> Result<i64, Error> {
+        let token = decode_address(&self.client.call(pool, selector).await?, 0)?;
+        self.client.decimals(&token).await
+    }
+
+    // pool token decimals are immutable, so they are requested once
+    async fn get_pool_decimals(&self, pool: &str) -> Result<PoolDecimals, Error> {
+        if let Some(decimals) = self.decimals.read().await.get(pool) {
+            return Ok(*decimals);
+        }
+
+        let decimals = PoolDecimals {
+            token0: self
+                .get_token_decimals(pool, UNISWAP_TOKEN0_SELECTOR)
+                .await?,
+            token1: self
+                .get_token_decimals(pool, UNISWAP_TOKEN1_SELECTOR)
+                .await?,
+        };
+        self.decimals
+            .write()
+            .await
+            .insert(pool.to_string(), decimals);
+        Ok(decimals)
+    }
+
+    async fn get_market_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let pool = ticker.ticker.as_str();
+        let decimals = self.get_pool_decimals(pool).await?;
+        let sqrt_price_x96 = decode_uint(
+            &self
+                .client
+                .call(pool, &encode_call(UNISWAP_SLOT0_SELECTOR, &[]))
+                .await?,
+            0,
+        )?;
+        let liquidity = decode_uint(
+            &self
+                .client
+                .call(pool, &encode_call(UNISWAP_LIQUIDITY_SELECTOR, &[]))
+                .await?,
+            0,
+        )?;
+        let price = sqrt_price_to_price(&sqrt_price_x96, &decimals);
+
+        Ok(MarketData {
+            provider: UNISWAP_PROVIDER_NAME.to_string(),
+            ticker: if ticker.alias.trim().is_empty() {
+                ticker.ticker.clone()
+            } else {
+                ticker.alias
+            },
+            price: if ticker.inverted {
+                price.inverse()
+            } else {
+                price
+            },
+            volume: liquidity_volume(&sqrt_price_x96, &liquidity, &decimals, ticker.inverted),
+            timestamp: Utc::now().timestamp(),
+            book: None,
+            candle: None,
+            latency_ms: None,
+        })
+    }
+}
+
+// token0 price in token1: (sqrtPriceX96 / 2^96)^2 * 10^(decimals0 - decimals1)
+fn sqrt_price_to_price(sqrt_price_x96: &BigInt, decimals: &PoolDecimals) -> BigDecimal {
+    let q192 = BigInt::from(1) << (2 * Q96_BITS);
+    BigDecimal::from(sqrt_price_x96 * sqrt_price_x96) / BigDecimal::from(q192)
+        * BigDecimal::new(BigInt::from(1), decimals.token1 - decimals.token0)
+}
+
+// in-range virtual reserve of the price quote token, used as volume weight:
+// token1 reserve L * sqrtPrice, or token0 reserve L / sqrtPrice for inverted tickers
+fn liquidity_volume(
+    sqrt_price_x96: &BigInt,
+    liquidity: &BigInt,
+    decimals: &PoolDecimals,
+    inverted: bool,
+) -> BigDecimal {
+    let q96 = BigInt::from(1) << Q96_BITS;
+    if inverted {
+        BigDecimal::from(liquidity * q96) / BigDecimal::from(sqrt_price_x96.clone())
+            * BigDecimal::new(BigInt::from(1), decimals.token0)
+    } else {
+        BigDecimal::from(liquidity * sqrt_price_x96) / BigDecimal::from(q96)
+            * BigDecimal::new(BigInt::from(1), decimals.token1)
+    }
+}
+
+#[async_trait]
+impl TickerCollector for UniswapMarketDataCollector {
+    fn name(&self) -> &'static str {
+        UNISWAP_PROVIDER_NAME
+    }
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        self.get_market_data(ticker).await
+    }
+}
+
+#[async_trait]
+impl MarketDataCollector for UniswapMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let schedule = self.schedule.clone();
+        let (max_in_flight, request_delay) = (self.max_in_flight, self.request_delay);
+        poll_tickers(self, schedule, max_in_flight, request_delay, tx).await
+    }
+
+    // pool is valid if its tokens decimals can be read
+    async fn validate(&self) -> Result<(), Error> {
+        let rejected = self
+            .schedule
+            .reject(|pool| async move { self.get_pool_decimals(&pool).await.map(|_| ()) })
+            .await?;
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Collector(format!(
+                "uniswap tickers are rejected: {}",
+                rejected.join(", ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{liquidity_volume, sqrt_price_to_price, PoolDecimals, UniswapMarketDataCollector};
+    use crate::config::CollectorConfig;
+    use bigdecimal::num_bigint::BigInt;
+    use bigdecimal::{BigDecimal, Zero};
+    use config::{Config, File, FileFormat};
+    use std::str::FromStr;
+
+    #[test]
+    fn sqrt_price() {
+        let q96 = BigInt::from(1) << 96;
+        let decimals = PoolDecimals {
+            token0: 18,
+            token1: 18,
+        };
+        assert_eq!(
+            sqrt_price_to_price(&(&q96 * 2), &decimals),
+            BigDecimal::from(4)
+        );
+
+        // 6 decimals token0 (USDC) priced in 18 decimals token1 (WETH)
+        let decimals = PoolDecimals {
+            token0: 6,
+            token1: 18,
+        };
+        assert_eq!(
+            sqrt_price_to_price(&(&q96 * 2_000_000), &decimals),
+            BigDecimal::from(4)
+        );
+    }
+
+    #[test]
+    fn quote_volume() {
+        let q96 = BigInt::from(1) << 96;
+        // token0 priced 4 token1
+        let decimals = PoolDecimals {
+            token0: 6,
+            token1: 18,
+        };
+        let sqrt_price_x96 = &q96 * 2_000_000;
+        let liquidity = BigInt::from(10).pow(12);
+        // token1 reserve L * sqrtP
+        assert_eq!(
+            liquidity_volume(&sqrt_price_x96, &liquidity, &decimals, false),
+            BigDecimal::from(2)
+        );
+        // token0 reserve L / sqrtP
+        assert_eq!(
+            liquidity_volume(&sqrt_price_x96, &liquidity, &decimals, true),
+            BigDecimal::from_str("0.5").unwrap()
+        );
+    }
+
+    // requires mainnet fork node, e.g. `anvil --fork-url <rpc>`
+    #[tokio::test]
+    #[ignore]
+    async fn anvil_pool() {
+        let endpoint =
+            std::env::var("ANVIL_ENDPOINT").unwrap_or_else(|_| "http://localhost:8545".to_string());
+        let config: CollectorConfig = Config::builder()
+            .add_source(File::from_str(
+                &format!(
+                    r#"
+kind: uniswap
+enabled: true
+endpoint: "{}"
+delay:
+  batch: 5s
+  request: 100ms
+tickers:
+  - ticker: "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"
+    alias: "ETH"
+    inverted: true
+"#,
+                    endpoint
+                ),
+                FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        let collector = UniswapMarketDataCollector::new(&config).unwrap();
+        let data = collector
+            .get_market_data(config.tickers[0].clone())
+            .await
+            .unwrap();
+        assert_eq!(data.ticker, "ETH");
+        assert!(data.price > BigDecimal::zero());
+        assert!(data.volume > BigDecimal::zero());
+    }
+}
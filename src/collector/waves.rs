@@ -0,0 +1,362 @@
+use super::schedule::TickerSchedule;
+use super::{poll_tickers, vwap, MarketData, MarketDataCollector, TickerCollector, Trade};
+use crate::config::{CollectorConfig, CollectorMode, Ticker};
+use crate::error::Error;
+use crate::http::build_client;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+const WAVES_PROVIDER_NAME: &str = "waves";
+const WAVES_TRADES_LIMIT: u32 = 100;
+// window trades are fetched page by page, too liquid pairs fail instead of going stale
+const WAVES_TRADES_MAX_PAGES: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponsePairData {
+    #[serde(rename(deserialize = "lastPrice"))]
+    last_price: Option<BigDecimal>,
+    #[serde(rename(deserialize = "quoteVolume"))]
+    volume: Option<BigDecimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponsePair {
+    data: Option<ResponsePairData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseExchangeData {
+    price: BigDecimal,
+    amount: BigDecimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseExchange {
+    data: ResponseExchangeData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseExchanges {
+    data: Vec<ResponseExchange>,
+    #[serde(rename(deserialize = "lastCursor"), default)]
+    last_cursor: Option<String>,
+    #[serde(rename(deserialize = "isLastPage"), default)]
+    is_last_page: bool,
+}
+
+// waves dex collector over data service api,
+// ticker is a pair of asset ids `AMOUNT_ASSET/PRICE_ASSET` (`WAVES` for native token)
+#[derive(Debug, Clone)]
+pub struct WavesMarketDataCollector {
+    endpoint: String,
+    mode: CollectorMode,
+    matcher: Option<String>,
+    window: Duration,
+    schedule: TickerSchedule,
+    request_delay: Duration,
+    max_in_flight: usize,
+    client: Client,
+}
+
+impl WavesMarketDataCollector {
+    pub fn new(config: &CollectorConfig) -> Result<Self, Error> {
+        match config.mode {
+            CollectorMode::Ticker | CollectorMode::Trades => {}
+            mode => {
+                return Err(Error::Collector(format!(
+                    "unsupported waves collector mode: {:?}",
+                    mode
+                )))
+            }
+        };
+
+        Ok(WavesMarketDataCollector {
+            endpoint: config.endpoint.clone(),
+            mode: config.mode,
+            matcher: config.matcher.clone(),
+            window: config.window.into(),
+            schedule: TickerSchedule::new(&config.tickers, &config.delay)?,
+            request_delay: config.delay.request.into(),
+            max_in_flight: config.max_in_flight.max(1),
+            client: build_client(&config.http)?,
+        })
+    }
+
+    async fn get_market_data(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        let (amount_asset, price_asset) = ticker
+            .ticker
+            .split_once('/')
+            .ok_or_else(|| Error::Collector(format!("invalid waves pair: {}", ticker.ticker)))?;
+        let (price, volume) = match self.mode {
+            CollectorMode::Trades => self.get_trades(amount_asset, price_asset).await?,
+            _ => self.get_pair(amount_asset, price_asset).await?,
+        };
+        Ok(market_data(ticker, price, volume))
+    }
+
+    // 24h dex pair stats
+    async fn get_pair(
+        &self,
+        amount_asset: &str,
+        price_asset: &str,
+    ) -> Result<(BigDecimal, BigDecimal), Error> {
+        let mut url = format!(
+            "{}/v0/pairs/{}/{}",
+            self.endpoint, amount_asset, price_asset
+        );
+        if let Some(matcher) = &self.matcher {
+            url = format!("{}?matcher={}", url, matcher);
+        }
+        pair_price(self.get_json(url).await?)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        let res = self.client.get(url).send().await?;
+        let status = res.status();
+        decode_response(status, &res.bytes().await?)
+    }
+
+    // vwap of exchange transactions over window, pages are followed by cursor
+    async fn get_trades(
+        &self,
+        amount_asset: &str,
+        price_asset: &str,
+    ) -> Result<(BigDecimal, BigDecimal), Error> {
+        let url = format!(
+            "{}/v0/transactions/exchange?amountAsset={}&priceAsset={}&timeStart={}&sort=desc&limit={}",
+            self.endpoint,
+            amount_asset,
+            price_asset,
+            Utc::now().timestamp_millis() - self.window.as_millis() as i64,
+            WAVES_TRADES_LIMIT
+        );
+        let mut trades = vec![];
+        let mut page_url = url.clone();
+        for _ in 0..WAVES_TRADES_MAX_PAGES {
+            let page: ResponseExchanges = self.get_json(page_url).await?;
+            let cursor = next_cursor(&page);
+            trades.extend(page.data);
+            match cursor {
+                Some(cursor) => {
+                    let mut next = Url::parse(&url).map_err(|e| Error::Collector(e.to_string()))?;
+                    next.query_pairs_mut().append_pair("after", &cursor);
+                    page_url = next.to_string();
+                }
+                None => return trades_price(trades),
+            }
+        }
+        Err(Error::Collector(format!(
+            "waves {}/{} has more than {} trades in window",
+            amount_asset,
+            price_asset,
+            WAVES_TRADES_LIMIT as usize * WAVES_TRADES_MAX_PAGES
+        )))
+    }
+}
+
+fn market_data(ticker: Ticker, price: BigDecimal, volume: BigDecimal) -> MarketData {
+    MarketData {
+        provider: WAVES_PROVIDER_NAME.to_string(),
+        ticker: if ticker.alias.trim().is_empty() {
+            ticker.ticker
+        } else {
+            ticker.alias
+        },
+        price: if ticker.inverted {
+            price.inverse()
+        } else {
+            price
+        },
+        volume,
+        timestamp: Utc::now().timestamp(),
+        book: None,
+        candle: None,
+        latency_ms: None,
+    }
+}
+
+// last price and 24h quote volume, pairs without trades have no volume
+fn pair_price(res: ResponsePair) -> Result<(BigDecimal, BigDecimal), Error> {
+    let data = res
+        .data
+        .ok_or_else(|| Error::Collector(String::from("no waves pair data")))?;
+
+    Ok((
+        data.last_price
+            .ok_or_else(|| Error::Collector(String::from("no waves pair price")))?,
+        data.volume.unwrap_or_default(),
+    ))
+}
+
+// cursor of the next page, if the page is full and not the last one
+fn next_cursor(page: &ResponseExchanges) -> Option<String> {
+    if page.is_last_page || page.data.len() < WAVES_TRADES_LIMIT as usize {
+        return None;
+    }
+    page.last_cursor.clone()
+}
+
+// decodes response or data service error, bad request (like unknown asset) is permanent
+fn decode_response<T: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<T, Error> {
+    if status.is_success() {
+        return Ok(serde_json::from_slice(body)?);
+    }
+
+    match serde_json::from_slice::<ResponseError>(body) {
+        Ok(e) => Err(Error::Exchange {
+            exchange: WAVES_PROVIDER_NAME.to_string(),
+            status: status.as_u16(),
+            code: 0,
+            message: e.message,
+            permanent: status == StatusCode::BAD_REQUEST || status == StatusCode::NOT_FOUND,
+        }),
+        Err(_) => Err(Error::Collector(format!("waves http status {}", status))),
+    }
+}
+
+fn trades_price(trades: Vec<ResponseExchange>) -> Result<(BigDecimal, BigDecimal), Error> {
+    vwap(
+        &trades
+            .into_iter()
+            .map(|tx| Trade {
+                price: tx.data.price,
+                amount: tx.data.amount,
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[async_trait]
+impl TickerCollector for WavesMarketDataCollector {
+    fn name(&self) -> &'static str {
+        WAVES_PROVIDER_NAME
+    }
+
+    async fn fetch_ticker(&self, ticker: Ticker) -> Result<MarketData, Error> {
+        self.get_market_data(ticker).await
+    }
+}
+
+#[async_trait]
+impl MarketDataCollector for WavesMarketDataCollector {
+    async fn collect(self: Arc<Self>, tx: Sender<MarketData>) {
+        let schedule = self.schedule.clone();
+        let (max_in_flight, request_delay) = (self.max_in_flight, self.request_delay);
+        poll_tickers(self, schedule, max_in_flight, request_delay, tx).await
+    }
+}
+
+#[test]
+fn test_pair_price() {
+    use std::str::FromStr;
+
+    let res: ResponsePair = serde_json::from_str(
+        r#"{"__type":"pair","amountAsset":"WAVES","priceAsset":"DG2xFkPdDwKUoBkzGAhQtLpSGzfXLiCYPEzeKH2Ad24p","data":{"firstPrice":1.6,"lastPrice":1.65,"low":1.55,"high":1.7,"weightedAveragePrice":1.62,"volume":1000,"quoteVolume":1620.5,"volumeWaves":1000,"txsCount":42}}"#,
+    )
+    .unwrap();
+    let (price, volume) = pair_price(res).unwrap();
+    assert_eq!(price, BigDecimal::from_str("1.65").unwrap());
+    assert_eq!(volume, BigDecimal::from_str("1620.5").unwrap());
+
+    // pair without trades
+    let res: ResponsePair =
+        serde_json::from_str(r#"{"__type":"pair","data":{"lastPrice":null,"quoteVolume":null}}"#)
+            .unwrap();
+    assert!(pair_price(res).is_err());
+    let res: ResponsePair = serde_json::from_str(r#"{"__type":"pair","data":null}"#).unwrap();
+    assert!(pair_price(res).is_err());
+}
+
+#[test]
+fn test_trades_price() {
+    use std::str::FromStr;
+
+    let res: ResponseExchanges = serde_json::from_str(
+        r#"{"__type":"list","lastCursor":"cursor","data":[
+            {"__type":"transaction","data":{"id":"a","timestamp":"2022-04-20T10:00:01.000Z","price":2,"amount":1,"orderType":"buy"}},
+            {"__type":"transaction","data":{"id":"b","timestamp":"2022-04-20T10:00:00.000Z","price":"2.5","amount":3,"orderType":"sell"}}
+        ]}"#,
+    )
+    .unwrap();
+    // short page is the last one
+    assert!(next_cursor(&res).is_none());
+    let (price, volume) = trades_price(res.data).unwrap();
+    assert_eq!(price, BigDecimal::from_str("2.375").unwrap());
+    assert_eq!(volume, BigDecimal::from_str("9.5").unwrap());
+
+    let res: ResponseExchanges = serde_json::from_str(r#"{"__type":"list","data":[]}"#).unwrap();
+    assert!(trades_price(res.data).is_err());
+}
+
+#[test]
+fn test_trades_pages() {
+    let page = |is_last_page: bool| ResponseExchanges {
+        data: (0..WAVES_TRADES_LIMIT)
+            .map(|_| ResponseExchange {
+                data: ResponseExchangeData {
+                    price: BigDecimal::from(1),
+                    amount: BigDecimal::from(1),
+                },
+            })
+            .collect(),
+        last_cursor: Some("cursor".to_string()),
+        is_last_page,
+    };
+    assert_eq!(next_cursor(&page(false)).unwrap(), "cursor");
+    assert!(next_cursor(&page(true)).is_none());
+}
+
+#[test]
+fn test_decode_error() {
+    let err = decode_response::<ResponsePair>(
+        StatusCode::BAD_REQUEST,
+        br#"{"message":"Validation Error","errors":[{"code":1,"message":"invalid asset id"}]}"#,
+    )
+    .unwrap_err();
+    assert!(err.is_permanent());
+    let err =
+        decode_response::<ResponsePair>(StatusCode::BAD_GATEWAY, b"<html></html>").unwrap_err();
+    assert!(!err.is_permanent());
+}
+
+#[test]
+fn test_market_data() {
+    use std::str::FromStr;
+
+    let ticker = |alias: &str, inverted: bool| Ticker {
+        ticker: "WAVES/USDN".to_string(),
+        alias: alias.to_string(),
+        inverted,
+        interval: None,
+        class: None,
+        price: None,
+        volume: None,
+        walk: None,
+        schedule: vec![],
+    };
+    let data = market_data(ticker("", false), BigDecimal::from(4), BigDecimal::from(10));
+    assert_eq!(data.ticker, "WAVES/USDN");
+    assert_eq!(data.price, BigDecimal::from(4));
+    assert_eq!(data.volume, BigDecimal::from(10));
+
+    let data = market_data(
+        ticker("USDNWAVES", true),
+        BigDecimal::from(4),
+        BigDecimal::from(10),
+    );
+    assert_eq!(data.ticker, "USDNWAVES");
+    assert_eq!(data.price, BigDecimal::from_str("0.25").unwrap());
+    assert_eq!(data.volume, BigDecimal::from(10));
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    // listen address, e.g. `0.0.0.0:8080`
+    pub address: String,
+}
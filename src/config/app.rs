@@ -1,4 +1,4 @@
-use super::{CollectorConfig, PriceOracleConfig, ProvidersConfig};
+use super::{ApiConfig, CollectorConfig, PriceOracleConfig, ProvidersConfig, RecorderConfig};
 use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
 
@@ -7,4 +7,8 @@ pub struct AppConfig {
     pub providers: ProvidersConfig,
     pub collectors: Vec<CollectorConfig>,
     pub oracle: PriceOracleConfig,
+    #[serde(default)]
+    pub recorder: Option<RecorderConfig>,
+    #[serde(default)]
+    pub api: Option<ApiConfig>,
 }
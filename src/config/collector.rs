@@ -1,13 +1,72 @@
 use super::delay::DelayConfig;
+use super::http::HttpConfig;
+use super::mode::CollectorMode;
 use super::ticker::Ticker;
+use bigdecimal::BigDecimal;
+use duration_string::DurationString;
 use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
+use std::time::Duration;
+
+const DEFAULT_TRADES_WINDOW: Duration = Duration::from_secs(60);
+const DEFAULT_KLINES_INTERVAL: &str = "1m";
+const DEFAULT_REPLAY_SPEED: f64 = 1.0;
+const DEFAULT_MAX_IN_FLIGHT: usize = 1;
+const DEFAULT_VALIDATE_INTERVAL: Duration = Duration::from_secs(3600);
 
 #[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
 pub struct CollectorConfig {
     pub kind: String,
     pub enabled: bool,
+    #[serde(default)]
+    pub mode: CollectorMode,
     pub endpoint: String,
     pub delay: DelayConfig,
     pub tickers: Vec<Ticker>,
+    #[serde(default)]
+    pub http: HttpConfig,
+    // max relative spread (ask - bid) / mid accepted in book mode
+    #[serde(default)]
+    pub max_spread: Option<BigDecimal>,
+    // recent trades window in trades mode
+    #[serde(default = "default_window")]
+    pub window: DurationString,
+    // kline/candle interval in klines mode
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    // waves matcher address for dex pair stats
+    #[serde(default)]
+    pub matcher: Option<String>,
+    // market data ttl of collector, overrides oracle ttl
+    #[serde(default)]
+    pub ttl: Option<DurationString>,
+    // replay speed multiplier of recorded market data
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    // max concurrent ticker requests
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    // interval of tickers validation against venue metadata
+    #[serde(default = "default_validate_interval")]
+    pub validate_interval: DurationString,
+}
+
+fn default_window() -> DurationString {
+    DEFAULT_TRADES_WINDOW.into()
+}
+
+fn default_interval() -> String {
+    DEFAULT_KLINES_INTERVAL.to_string()
+}
+
+fn default_speed() -> f64 {
+    DEFAULT_REPLAY_SPEED
+}
+
+fn default_max_in_flight() -> usize {
+    DEFAULT_MAX_IN_FLIGHT
+}
+
+fn default_validate_interval() -> DurationString {
+    DEFAULT_VALIDATE_INTERVAL.into()
 }
@@ -1,9 +1,13 @@
 use duration_string::DurationString;
 use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
 pub struct DelayConfig {
     pub request: DurationString,
     pub batch: DurationString,
+    // polling intervals of ticker priority classes
+    #[serde(default)]
+    pub classes: HashMap<String, DurationString>,
 }
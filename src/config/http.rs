@@ -0,0 +1,36 @@
+use duration_string::DurationString;
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub connect_timeout: DurationString,
+    pub timeout: DurationString,
+    pub proxy: Option<String>,
+    pub ca_cert: Option<String>,
+    pub user_agent: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<DurationString>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT.into(),
+            timeout: DEFAULT_REQUEST_TIMEOUT.into(),
+            proxy: None,
+            ca_cert: None,
+            user_agent: None,
+            headers: HashMap::new(),
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+        }
+    }
+}
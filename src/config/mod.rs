@@ -1,12 +1,27 @@
+mod api;
 mod app;
 mod collector;
 mod delay;
+mod http;
+mod mode;
 mod oracle;
 mod provider;
+mod recorder;
+mod scheme;
+mod signer;
 mod ticker;
 
+pub use api::ApiConfig;
 pub use app::AppConfig;
 pub use collector::CollectorConfig;
+pub use delay::DelayConfig;
+pub use http::HttpConfig;
+pub use mode::CollectorMode;
 pub use oracle::PriceOracleConfig;
-pub use provider::{P2PProxyProviderConfig, ProvidersConfig};
-pub use ticker::Ticker;
+pub use provider::{
+    GrpcProviderConfig, P2PProxyProviderConfig, ProvidersConfig, WebSocketProviderConfig,
+};
+pub use recorder::RecorderConfig;
+pub use scheme::SignatureScheme;
+pub use signer::{RemoteSignerConfig, SignerConfig};
+pub use ticker::{ScheduledPrice, Ticker};
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, SlogValue)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectorMode {
+    // 24h ticker last price weighted by 24h volume
+    #[default]
+    Ticker,
+    // top of book mid price
+    Book,
+    // vwap of recent trades over window
+    Trades,
+    // last closed kline/candle
+    Klines,
+}
@@ -6,4 +6,10 @@ use slog_extlog_derive::SlogValue;
 pub struct PriceOracleConfig {
     pub delay: DurationString,
     pub ttl: DurationString,
+    // file persisting round id and ticker sequence numbers across restarts
+    #[serde(default)]
+    pub state_path: Option<String>,
+    // published oracle instance id, `HOSTNAME` env if not set
+    #[serde(default)]
+    pub instance_id: Option<String>,
 }
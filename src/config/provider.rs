@@ -1,13 +1,44 @@
+use super::http::HttpConfig;
+use super::signer::SignerConfig;
 use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
 
 #[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
 pub struct ProvidersConfig {
     pub p2p: Vec<P2PProxyProviderConfig>,
+    #[serde(default)]
+    pub grpc: Option<GrpcProviderConfig>,
+    #[serde(default)]
+    pub websocket: Option<WebSocketProviderConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
 pub struct P2PProxyProviderConfig {
     pub topic: String,
     pub endpoint: String,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub signer: Option<SignerConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct GrpcProviderConfig {
+    // listen address, e.g. `0.0.0.0:50051`
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct WebSocketProviderConfig {
+    // listen address, e.g. `0.0.0.0:8081`
+    pub address: String,
+    // rounds queued per client, slow clients skip older ones
+    #[serde(default = "default_buffer")]
+    pub buffer: usize,
+}
+
+const DEFAULT_WEBSOCKET_BUFFER: usize = 16;
+
+fn default_buffer() -> usize {
+    DEFAULT_WEBSOCKET_BUFFER
 }
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct RecorderConfig {
+    pub enabled: bool,
+    // directory of recorded jsonl files
+    pub path: String,
+    // file size in bytes to rotate at
+    pub max_size: u64,
+    // number of kept files, at least 1
+    pub max_files: usize,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, SlogValue)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    Ed25519,
+    // ecdsa over sha256 of message
+    Secp256k1,
+    // waves curve25519, verifiable with ride `sigVerify`
+    Waves,
+}
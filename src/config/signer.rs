@@ -0,0 +1,51 @@
+use super::{HttpConfig, SignatureScheme};
+use duration_string::DurationString;
+use serde::{Deserialize, Serialize};
+use slog_extlog_derive::SlogValue;
+use std::time::Duration;
+
+// exactly one of `key`, `key_file`, `key_env` and `remote` key sources is expected
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct SignerConfig {
+    pub scheme: SignatureScheme,
+    // hex encoded private key, base58 for waves scheme
+    #[serde(default)]
+    pub key: Option<String>,
+    // file with encoded private key or encrypted json keystore
+    #[serde(default)]
+    pub key_file: Option<String>,
+    // env variable with encoded private key
+    #[serde(default)]
+    pub key_env: Option<String>,
+    // env variable with keystore password
+    #[serde(default)]
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub remote: Option<RemoteSignerConfig>,
+    #[serde(default)]
+    pub key_id: String,
+    // domain separator, distinguishes our messages from other publishers
+    #[serde(default = "default_domain")]
+    pub domain: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, SlogValue)]
+pub struct RemoteSignerConfig {
+    pub endpoint: String,
+    #[serde(default)]
+    pub http: HttpConfig,
+    // how long public key is cached, so rotated keys are picked up
+    #[serde(default = "default_key_ttl")]
+    pub key_ttl: DurationString,
+}
+
+const DEFAULT_DOMAIN: &str = "price-oracle";
+const DEFAULT_KEY_TTL: Duration = Duration::from_secs(300);
+
+fn default_domain() -> String {
+    DEFAULT_DOMAIN.to_string()
+}
+
+fn default_key_ttl() -> DurationString {
+    DEFAULT_KEY_TTL.into()
+}
@@ -1,3 +1,5 @@
+use bigdecimal::BigDecimal;
+use duration_string::DurationString;
 use serde::{Deserialize, Serialize};
 use slog_extlog_derive::SlogValue;
 
@@ -6,4 +8,29 @@ pub struct Ticker {
     pub ticker: String,
     pub alias: String,
     pub inverted: bool,
+    // polling interval, overrides priority class and batch delay
+    #[serde(default)]
+    pub interval: Option<DurationString>,
+    // priority class of collector delay config
+    #[serde(default)]
+    pub class: Option<String>,
+    // fixed price of static collector
+    #[serde(default)]
+    pub price: Option<BigDecimal>,
+    // volume weight of static collector price
+    #[serde(default)]
+    pub volume: Option<BigDecimal>,
+    // max relative random step of static price per batch
+    #[serde(default)]
+    pub walk: Option<BigDecimal>,
+    // scheduled static price changes
+    #[serde(default)]
+    pub schedule: Vec<ScheduledPrice>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledPrice {
+    // delay since collector start
+    pub after: DurationString,
+    pub price: BigDecimal,
 }
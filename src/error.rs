@@ -11,12 +11,63 @@ pub enum Error {
     Provider(String),
     #[error("collector error: {0}")]
     Collector(String),
+    #[error("signer error: {0}")]
+    Signer(String),
+    #[error("http server error: {0}")]
+    HttpServer(String),
+    #[error("http client error: {0}")]
+    HttpClient(String),
+    #[error("{exchange} error {code}: {message} (http status {status})")]
+    Exchange {
+        exchange: String,
+        status: u16,
+        code: i64,
+        message: String,
+        // misconfiguration like invalid symbol, retry won't help
+        permanent: bool,
+    },
     #[error("reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
     #[error("parse float error: {0}")]
     ParseFloat(#[from] ParseFloatError),
     #[error("encoded error: {0}")]
     Encode(#[from] EncodeError),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("IO error: {0}")]
     IO(#[from] std::io::Error),
+    #[error("metrics error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
+impl Error {
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Error::Exchange {
+                permanent: true,
+                ..
+            }
+        )
+    }
+
+    // short error type, used as metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::ConfigLoad(_) => "config",
+            Error::Provider(_) => "provider",
+            Error::Collector(_) => "collector",
+            Error::Signer(_) => "signer",
+            Error::HttpServer(_) => "http_server",
+            Error::HttpClient(_) => "http_client",
+            Error::Exchange { .. } => "exchange",
+            Error::Reqwest(e) if e.is_timeout() => "timeout",
+            Error::Reqwest(_) => "http",
+            Error::ParseFloat(_) => "parse",
+            Error::Encode(_) => "encode",
+            Error::Json(_) => "json",
+            Error::IO(_) => "io",
+            Error::Metrics(_) => "metrics",
+        }
+    }
 }
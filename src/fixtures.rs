@@ -0,0 +1,51 @@
+// test fixtures shared by modules
+use crate::collector::MarketData;
+use crate::round::{PriceRound, RoundPrice};
+use bigdecimal::BigDecimal;
+
+// rfc 8032 ed25519 test 1 key pair, hex encoded
+pub const ED25519_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+pub const ED25519_PUBLIC_KEY: &str =
+    "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+// market data without book, candle and latency
+pub fn market_data(
+    provider: &str,
+    ticker: &str,
+    price: BigDecimal,
+    volume: BigDecimal,
+    timestamp: i64,
+) -> MarketData {
+    MarketData {
+        provider: provider.to_string(),
+        ticker: ticker.to_string(),
+        price,
+        volume,
+        timestamp,
+        book: None,
+        candle: None,
+        latency_ms: None,
+    }
+}
+
+// round of binance prices 42 of tickers, price sequences are round id
+pub fn round(id: u64, tickers: &[&str]) -> PriceRound {
+    PriceRound {
+        id,
+        instance_id: "oracle".to_string(),
+        prices: tickers
+            .iter()
+            .map(|ticker| RoundPrice {
+                sequence: id,
+                data: market_data(
+                    "binance",
+                    ticker,
+                    BigDecimal::from(42),
+                    BigDecimal::from(1),
+                    0,
+                ),
+                sources: vec!["binance".to_string()],
+            })
+            .collect(),
+    }
+}
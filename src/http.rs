@@ -0,0 +1,43 @@
+use crate::config::HttpConfig;
+use crate::error::Error;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Client, Proxy};
+use std::fs;
+
+// build http client from component http config
+pub fn build_client(config: &HttpConfig) -> Result<Client, Error> {
+    let mut builder = Client::builder()
+        .connect_timeout(config.connect_timeout.into())
+        .timeout(config.timeout.into());
+
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+    if let Some(ca_cert) = &config.ca_cert {
+        let pem = fs::read(ca_cert)?;
+        builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+    if let Some(user_agent) = &config.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if !config.headers.is_empty() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| Error::HttpClient(format!("invalid header {}: {}", name, e)))?,
+                HeaderValue::from_str(value)
+                    .map_err(|e| Error::HttpClient(format!("invalid header {}: {}", name, e)))?,
+            );
+        }
+        builder = builder.default_headers(headers);
+    }
+    if let Some(max_idle) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(idle_timeout) = config.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(Some(idle_timeout.into()));
+    }
+
+    Ok(builder.build()?)
+}
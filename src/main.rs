@@ -1,6 +1,8 @@
 use crate::aggregator::PriceAggregator;
-use crate::collector::{MarketData, MarketDataVec};
+use crate::collector::{provider_ttls, MarketData, MarketDataVec};
 use crate::provider::init_providers;
+use crate::recorder::MarketDataRecorder;
+use crate::round::RoundSequencer;
 use crate::{collector::init_collectors, config::AppConfig, error::Error};
 use futures::future::try_join_all;
 use pepe_config::load;
@@ -11,12 +13,24 @@ use tokio::sync::RwLock;
 use tokio::time::sleep;
 
 mod aggregator;
+mod api;
 mod collector;
 mod config;
 mod error;
+#[cfg(test)]
+mod fixtures;
+mod http;
+mod metrics;
+mod proto;
 mod provider;
+mod recorder;
+mod round;
+mod signer;
 
 const DEFAULT_CONFIG_PATH: &str = include_str!("../config.yaml");
+const MARKET_DATA_CHANNEL_CAPACITY: usize = 100;
+// static collector prices pin tickers
+const STATIC_COLLECTOR_KIND: &str = "static";
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -27,11 +41,45 @@ async fn main() -> Result<(), Error> {
 
     let collectors = init_collectors(&app_config.collectors)?;
     let providers = init_providers(&app_config.providers)?;
-    let price_oracle = Arc::new(RwLock::new(PriceAggregator::new(
-        &app_config.oracle.ttl.into(),
-    )));
+    let mut aggregator = PriceAggregator::new(&app_config.oracle.ttl.into());
+    for (provider, ttl) in provider_ttls(&app_config.collectors)? {
+        aggregator.set_provider_ttl(&provider, &ttl);
+    }
+    for collector_config in &app_config.collectors {
+        if collector_config.kind == STATIC_COLLECTOR_KIND {
+            aggregator.set_pinning_provider(&collector_config.kind);
+        }
+    }
+    let price_oracle = Arc::new(RwLock::new(aggregator));
+    let instance_id = app_config
+        .oracle
+        .instance_id
+        .clone()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default();
+    let mut sequencer = RoundSequencer::new(&instance_id, app_config.oracle.state_path.as_deref())?;
+    let recorder = match &app_config.recorder {
+        Some(config) if config.enabled => Some(MarketDataRecorder::new(config)?.spawn()),
+        _ => None,
+    };
+
+    if let Some(config) = app_config.api.clone().filter(|config| config.enabled) {
+        let server = api::serve(&config, price_oracle.clone())?;
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                error!("api server stopped: {}", e);
+            }
+        });
+    }
 
-    let (tx, mut rx) = mpsc::channel::<MarketData>(100);
+    for collector in &collectors {
+        if let Err(e) = collector.validate().await {
+            error!("collector validation failed: {}", e);
+        }
+    }
+
+    let (tx, mut rx) = mpsc::channel::<MarketData>(MARKET_DATA_CHANNEL_CAPACITY);
+    metrics::register_channel_depth(tx.clone())?;
     for collector in &collectors {
         let sender = tx.clone();
         let collector = collector.clone();
@@ -45,23 +93,34 @@ async fn main() -> Result<(), Error> {
     tokio::spawn(async move {
         loop {
             sleep(app_config.oracle.delay.into()).await;
-            let oracle = price_oracle_consumer.read().await;
-            match oracle.aggregate() {
-                Ok(prices) => {
-                    info!("new market data"; "prices" => MarketDataVec{prices: prices.clone()});
-
-                    if let Err(e) = try_join_all(
-                        providers
-                            .iter()
-                            .map(|provider| provider.send(&prices))
-                            .collect::<Vec<_>>(),
-                    )
-                    .await
-                    {
-                        error!("can't feed prices: {}", e)
-                    };
+            // aggregated once, metrics and the round are made of the same prices
+            let (fresh_prices, prices) = price_oracle_consumer.read().await.aggregate();
+            metrics::observe_prices(&fresh_prices);
+            info!("new market data"; "prices" => MarketDataVec{
+                prices: prices.iter().map(|price| price.data.clone()).collect()
+            });
+            let round = match sequencer.next(prices) {
+                Ok(round) => round,
+                Err(e) => {
+                    error!("can't number prices round: {}", e);
+                    continue;
                 }
-                Err(e) => error!("can't collect prices: {}", e),
+            };
+
+            let round = &round;
+            if let Err(e) = try_join_all(
+                providers
+                    .iter()
+                    .map(|provider| async move {
+                        let result = provider.send(round).await;
+                        metrics::observe_publish(provider.name(), &result);
+                        result
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .await
+            {
+                error!("can't feed prices: {}", e)
             };
         }
     });
@@ -71,6 +130,11 @@ async fn main() -> Result<(), Error> {
             data = rx.recv() => {
                 match data {
                 Some(market_data) => {
+                    if let Some(recorder) = recorder.as_ref() {
+                        if let Err(e) = recorder.record(&market_data) {
+                            error!("can't record market data: {}", e);
+                        }
+                    }
                     let mut oracle = price_oracle.write().await;
                     oracle.consume(&market_data);
                 }
@@ -0,0 +1,193 @@
+use crate::aggregator::AggregatedPrice;
+use crate::collector::MarketData;
+use crate::error::Error;
+use bigdecimal::ToPrimitive;
+use chrono::Utc;
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{
+    register_gauge_vec, register_histogram_vec, register_int_counter_vec, Encoder, GaugeVec,
+    HistogramVec, IntCounterVec, IntGauge, TextEncoder,
+};
+use std::sync::LazyLock;
+use tokio::sync::mpsc::Sender;
+use tokio::time::Instant;
+
+static COLLECTOR_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "price_oracle_collector_requests_total",
+        "Market data requests made by collectors",
+        &["collector"]
+    )
+    .expect("collector requests metric")
+});
+
+static COLLECTOR_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "price_oracle_collector_errors_total",
+        "Failed market data requests by error kind",
+        &["collector", "kind"]
+    )
+    .expect("collector errors metric")
+});
+
+static COLLECTOR_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "price_oracle_collector_request_duration_seconds",
+        "Market data request duration",
+        &["collector"]
+    )
+    .expect("collector latency metric")
+});
+
+static COLLECTOR_LAST_SUCCESS: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "price_oracle_collector_last_success_timestamp_seconds",
+        "Unix time of the last successful market data request",
+        &["collector"]
+    )
+    .expect("collector last success metric")
+});
+
+static PRICE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!("price_oracle_price", "Aggregated ticker price", &["ticker"])
+        .expect("price metric")
+});
+
+static PRICE_SOURCES: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "price_oracle_price_sources",
+        "Number of fresh sources aggregated ticker price is made of",
+        &["ticker"]
+    )
+    .expect("price sources metric")
+});
+
+static PROVIDER_PUBLISHES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "price_oracle_provider_publishes_total",
+        "Rounds published by providers by result",
+        &["provider", "result"]
+    )
+    .expect("provider publishes metric")
+});
+
+// market data queued in collectors channel, sampled on scrape
+struct ChannelDepth {
+    tx: Sender<MarketData>,
+    gauge: IntGauge,
+}
+
+impl Collector for ChannelDepth {
+    fn desc(&self) -> Vec<&Desc> {
+        self.gauge.desc()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.gauge
+            .set((self.tx.max_capacity() - self.tx.capacity()) as i64);
+        self.gauge.collect()
+    }
+}
+
+// counts collector request, its duration and error kind
+pub fn observe_request<T>(collector: &str, started: Instant, result: &Result<T, Error>) {
+    COLLECTOR_REQUESTS.with_label_values(&[collector]).inc();
+    COLLECTOR_LATENCY
+        .with_label_values(&[collector])
+        .observe(started.elapsed().as_secs_f64());
+    match result {
+        Ok(_) => COLLECTOR_LAST_SUCCESS
+            .with_label_values(&[collector])
+            .set(Utc::now().timestamp_millis() as f64 / 1000.0),
+        Err(e) => COLLECTOR_ERRORS
+            .with_label_values(&[collector, e.kind()])
+            .inc(),
+    }
+}
+
+// replaces ticker gauges, so tickers without fresh data disappear
+pub fn observe_prices(prices: &[AggregatedPrice]) {
+    PRICE.reset();
+    PRICE_SOURCES.reset();
+    for price in prices {
+        if let Some(value) = price.data.price.to_f64() {
+            PRICE.with_label_values(&[&price.data.ticker]).set(value);
+        }
+        PRICE_SOURCES
+            .with_label_values(&[&price.data.ticker])
+            .set(price.sources.len() as f64);
+    }
+}
+
+pub fn observe_publish(provider: &str, result: &Result<(), Error>) {
+    let result = match result {
+        Ok(_) => "success",
+        Err(_) => "failure",
+    };
+    PROVIDER_PUBLISHES
+        .with_label_values(&[provider, result])
+        .inc();
+}
+
+pub fn register_channel_depth(tx: Sender<MarketData>) -> Result<(), Error> {
+    let gauge = IntGauge::new(
+        "price_oracle_market_data_channel_depth",
+        "Market data queued in collectors channel",
+    )?;
+    prometheus::register(Box::new(ChannelDepth { tx, gauge }))?;
+    Ok(())
+}
+
+// all metrics in prometheus text format
+pub fn encode() -> Result<String, Error> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    String::from_utf8(buffer).map_err(|e| Error::HttpServer(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode, observe_publish, observe_request, register_channel_depth};
+    use crate::error::Error;
+    use crate::fixtures::market_data;
+    use bigdecimal::BigDecimal;
+    use tokio::sync::mpsc;
+    use tokio::time::Instant;
+
+    #[test]
+    fn exposition() {
+        observe_request("metrics-test", Instant::now(), &Ok(()));
+        observe_request::<()>(
+            "metrics-test",
+            Instant::now(),
+            &Err(Error::Collector("down".to_string())),
+        );
+        observe_publish("metrics-test", &Ok(()));
+        let (tx, _rx) = mpsc::channel(10);
+        register_channel_depth(tx.clone()).unwrap();
+        tx.try_send(market_data(
+            "metrics-test",
+            "BTC",
+            BigDecimal::from(1),
+            BigDecimal::from(1),
+            0,
+        ))
+        .unwrap();
+
+        let metrics = encode().unwrap();
+        assert!(
+            metrics.contains("price_oracle_collector_requests_total{collector=\"metrics-test\"} 2")
+        );
+        assert!(metrics.contains(
+            "price_oracle_collector_errors_total{collector=\"metrics-test\",kind=\"collector\"} 1"
+        ));
+        assert!(metrics.contains(
+            "price_oracle_collector_request_duration_seconds_count{collector=\"metrics-test\"} 2"
+        ));
+        assert!(metrics.contains(
+            "price_oracle_provider_publishes_total{provider=\"metrics-test\",result=\"success\"} 1"
+        ));
+        assert!(metrics.contains("price_oracle_market_data_channel_depth 1"));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::Error;
+use crate::round::{PriceRound, RoundPrice};
+use bigdecimal::{BigDecimal, ToPrimitive};
+
+pub mod oracle {
+    include!(concat!(env!("OUT_DIR"), "/oracle.rs"));
+}
+
+// `Prices.version` of prices.proto
+pub const PRICES_SCHEMA_VERSION: u32 = 1;
+
+// unsigned prices message of round
+pub fn encode_prices(round: &PriceRound) -> Result<oracle::Prices, Error> {
+    Ok(oracle::Prices {
+        prices: round
+            .prices
+            .iter()
+            .map(encode_price)
+            .collect::<Result<_, Error>>()?,
+        round: round.id,
+        version: PRICES_SCHEMA_VERSION,
+        instance_id: round.instance_id.clone(),
+        ..Default::default()
+    })
+}
+
+pub fn encode_price(round_price: &RoundPrice) -> Result<oracle::Price, Error> {
+    let data = &round_price.data;
+    Ok(oracle::Price {
+        ticker: data.ticker.clone(),
+        price: to_f64(&data.price)?,
+        timestamp: data.timestamp,
+        sequence: round_price.sequence,
+        price_decimal: to_decimal_string(&data.price),
+        volume: to_f64(&data.volume)?,
+        volume_decimal: to_decimal_string(&data.volume),
+        sources: round_price.sources.clone(),
+        candle: data
+            .candle
+            .as_ref()
+            .map(|candle| -> Result<oracle::Candle, Error> {
+                Ok(oracle::Candle {
+                    open: to_f64(&candle.open)?,
+                    high: to_f64(&candle.high)?,
+                    low: to_f64(&candle.low)?,
+                    close: to_f64(&candle.close)?,
+                    volume: to_f64(&data.volume)?,
+                })
+            })
+            .transpose()?,
+    })
+}
+
+fn to_f64(value: &BigDecimal) -> Result<f64, Error> {
+    value
+        .to_f64()
+        .ok_or_else(|| Error::Provider("can't encode price".to_string()))
+}
+
+// plain notation without trailing zeros
+fn to_decimal_string(value: &BigDecimal) -> String {
+    value.normalized().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{oracle, to_decimal_string, PRICES_SCHEMA_VERSION};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn decimal_string() {
+        let price = BigDecimal::from_str("42580.020000").unwrap();
+        assert_eq!(to_decimal_string(&price), "42580.02");
+        let price = BigDecimal::from_str("0.00002356901234567890123").unwrap();
+        assert_eq!(to_decimal_string(&price), "0.00002356901234567890123");
+        assert_eq!(
+            BigDecimal::from_str(&to_decimal_string(&price.inverse())).unwrap(),
+            price.inverse()
+        );
+        let price = BigDecimal::from_str("1E+3").unwrap();
+        assert_eq!(to_decimal_string(&price), "1000");
+    }
+
+    // messages of the schema before versioning
+    mod legacy {
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct Price {
+            #[prost(string, tag = "1")]
+            pub ticker: String,
+            #[prost(double, tag = "2")]
+            pub price: f64,
+            #[prost(int64, tag = "4")]
+            pub timestamp: i64,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct Prices {
+            #[prost(message, repeated, tag = "1")]
+            pub prices: Vec<Price>,
+        }
+    }
+
+    #[test]
+    fn legacy_compatibility() {
+        use prost::Message;
+
+        let legacy_prices = legacy::Prices {
+            prices: vec![legacy::Price {
+                ticker: "BTC".to_string(),
+                price: 42580.02,
+                timestamp: 1650000000,
+            }],
+        };
+
+        // legacy message decoded with current schema
+        let prices = oracle::Prices::decode(legacy_prices.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(prices.version, 0);
+        assert_eq!(prices.prices.len(), 1);
+        assert_eq!(prices.prices[0].ticker, "BTC");
+        assert_eq!(prices.prices[0].price, 42580.02);
+        assert_eq!(prices.prices[0].timestamp, 1650000000);
+        assert!(prices.prices[0].price_decimal.is_empty());
+
+        // current message decoded by legacy decoder
+        let mut prices = prices;
+        prices.version = PRICES_SCHEMA_VERSION;
+        prices.round = 7;
+        prices.prices[0].price_decimal = "42580.02".to_string();
+        prices.prices[0].sources = vec!["binance".to_string()];
+        let decoded = legacy::Prices::decode(prices.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, legacy_prices);
+    }
+}
@@ -0,0 +1,230 @@
+use super::Provider;
+use crate::config::GrpcProviderConfig;
+use crate::error::Error;
+use crate::proto::encode_prices;
+use crate::proto::oracle::price_oracle_server::{PriceOracle, PriceOracleServer};
+use crate::proto::oracle::{GetPriceRequest, GetPricesRequest, Price, Prices, SubscribeRequest};
+use crate::round::PriceRound;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use pepe_log::error;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+// serves published rounds over grpc `PriceOracle` service,
+// rounds are encoded once on publishing and shared by all requests
+#[derive(Debug)]
+pub struct GrpcProvider {
+    rounds: watch::Sender<Option<Arc<Prices>>>,
+}
+
+impl GrpcProvider {
+    pub fn new(config: &GrpcProviderConfig) -> Result<Self, Error> {
+        let address: SocketAddr = config
+            .address
+            .parse()
+            .map_err(|e| Error::HttpServer(format!("invalid grpc address: {}", e)))?;
+        // bound before returning, so address errors fail startup
+        let listener = std::net::TcpListener::bind(address)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                TcpListener::from_std(listener)
+            })
+            .map_err(|e| Error::HttpServer(format!("can't bind grpc address: {}", e)))?;
+        let (rounds, receiver) = watch::channel(None);
+
+        tokio::spawn(async move {
+            if let Err(e) = Server::builder()
+                .add_service(PriceOracleServer::new(PriceOracleService {
+                    rounds: receiver,
+                }))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+            {
+                error!("grpc server stopped: {}", e);
+            }
+        });
+
+        Ok(GrpcProvider { rounds })
+    }
+}
+
+#[async_trait]
+impl Provider for GrpcProvider {
+    fn name(&self) -> &str {
+        "grpc"
+    }
+
+    async fn send(&self, round: &PriceRound) -> Result<(), Error> {
+        self.rounds
+            .send_replace(Some(Arc::new(encode_prices(round)?)));
+        Ok(())
+    }
+}
+
+struct PriceOracleService {
+    rounds: watch::Receiver<Option<Arc<Prices>>>,
+}
+
+type PricesStream = Pin<Box<dyn Stream<Item = Result<Prices, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl PriceOracle for PriceOracleService {
+    async fn get_prices(
+        &self,
+        _request: Request<GetPricesRequest>,
+    ) -> Result<Response<Prices>, Status> {
+        Ok(Response::new(round_prices(&self.rounds.borrow(), &[])))
+    }
+
+    async fn get_price(
+        &self,
+        request: Request<GetPriceRequest>,
+    ) -> Result<Response<Price>, Status> {
+        let ticker = request.into_inner().ticker;
+        round_prices(&self.rounds.borrow(), std::slice::from_ref(&ticker))
+            .prices
+            .pop()
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(format!("no price of {}", ticker)))
+    }
+
+    type SubscribeStream = PricesStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let tickers = request.into_inner().tickers;
+        let mut rounds = self.rounds.clone();
+        // the latest round is sent first, if any
+        let latest = round_prices(&rounds.borrow_and_update(), &tickers);
+
+        let updates = futures::stream::unfold(rounds, move |mut rounds| {
+            let tickers = tickers.clone();
+            async move {
+                loop {
+                    rounds.changed().await.ok()?;
+                    let prices = round_prices(&rounds.borrow_and_update(), &tickers);
+                    if !prices.prices.is_empty() {
+                        return Some((Ok(prices), rounds));
+                    }
+                }
+            }
+        });
+        let stream: PricesStream = if latest.prices.is_empty() {
+            Box::pin(updates)
+        } else {
+            Box::pin(futures::stream::once(async { Ok(latest) }).chain(updates))
+        };
+        Ok(Response::new(stream))
+    }
+}
+
+// round prices of tickers, all if tickers are empty, messages are not signed
+fn round_prices(round: &Option<Arc<Prices>>, tickers: &[String]) -> Prices {
+    let round = match round {
+        Some(round) => round,
+        None => return Prices::default(),
+    };
+    if tickers.is_empty() {
+        return round.as_ref().clone();
+    }
+    Prices {
+        prices: round
+            .prices
+            .iter()
+            .filter(|price| tickers.contains(&price.ticker))
+            .cloned()
+            .collect(),
+        round: round.round,
+        version: round.version,
+        instance_id: round.instance_id.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrpcProvider, PriceOracleService};
+    use crate::config::GrpcProviderConfig;
+    use crate::fixtures;
+    use crate::proto::encode_prices;
+    use crate::proto::oracle::price_oracle_server::PriceOracle;
+    use crate::proto::oracle::{GetPriceRequest, GetPricesRequest, Prices, SubscribeRequest};
+    use futures::StreamExt;
+    use std::sync::Arc;
+    use tokio::sync::watch;
+    use tonic::{Code, Request};
+
+    fn round(id: u64, tickers: &[&str]) -> Option<Arc<Prices>> {
+        Some(Arc::new(
+            encode_prices(&fixtures::round(id, tickers)).unwrap(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn service() {
+        let (rounds, receiver) = watch::channel(None);
+        let service = PriceOracleService { rounds: receiver };
+
+        let status = service
+            .get_price(Request::new(GetPriceRequest {
+                ticker: "BTC".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        rounds.send_replace(round(1, &["BTC", "ETH"]));
+        let prices = service
+            .get_prices(Request::new(GetPricesRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(prices.round, 1);
+        assert_eq!(prices.prices.len(), 2);
+        let price = service
+            .get_price(Request::new(GetPriceRequest {
+                ticker: "ETH".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(price.ticker, "ETH");
+
+        let mut stream = service
+            .subscribe(Request::new(SubscribeRequest {
+                tickers: vec!["ETH".to_string()],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let prices = stream.next().await.unwrap().unwrap();
+        assert_eq!(prices.round, 1);
+        assert_eq!(prices.prices.len(), 1);
+
+        // rounds without subscribed tickers are skipped
+        rounds.send_replace(round(2, &["BTC"]));
+        tokio::task::yield_now().await;
+        rounds.send_replace(round(3, &["BTC", "ETH"]));
+        let prices = stream.next().await.unwrap().unwrap();
+        assert_eq!(prices.round, 3);
+        assert_eq!(prices.prices[0].ticker, "ETH");
+    }
+
+    #[tokio::test]
+    async fn bind() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = GrpcProviderConfig {
+            address: listener.local_addr().unwrap().to_string(),
+        };
+        assert!(GrpcProvider::new(&config).is_err());
+    }
+}
@@ -1,23 +1,37 @@
 use std::sync::Arc;
 
-use crate::{collector::MarketData, config::ProvidersConfig, error::Error};
+use crate::{config::ProvidersConfig, error::Error, round::PriceRound};
 use async_trait::async_trait;
 
+use self::grpc::GrpcProvider;
 use self::p2p::P2PProvider;
+use self::websocket::WebSocketProvider;
 
+mod grpc;
 mod p2p;
+mod websocket;
 
 #[async_trait]
 pub trait Provider: Send + Sync {
-    async fn send(&self, prices: &[MarketData]) -> Result<(), Error>;
+    // provider kind, p2p ones with endpoint and topic, used as metrics label
+    fn name(&self) -> &str;
+
+    async fn send(&self, round: &PriceRound) -> Result<(), Error>;
 }
 
 pub fn init_providers(config: &ProvidersConfig) -> Result<Vec<Arc<dyn Provider>>, Error> {
-    config
+    let mut providers = config
         .p2p
         .iter()
         .map(|config| -> Result<Arc<dyn Provider>, Error> {
-            Ok(Arc::new(P2PProvider::new(config)))
+            Ok(Arc::new(P2PProvider::new(config)?))
         })
-        .collect::<Result<Vec<Arc<dyn Provider>>, Error>>()
+        .collect::<Result<Vec<Arc<dyn Provider>>, Error>>()?;
+    if let Some(config) = &config.grpc {
+        providers.push(Arc::new(GrpcProvider::new(config)?));
+    }
+    if let Some(config) = &config.websocket {
+        providers.push(Arc::new(WebSocketProvider::new(config)?));
+    }
+    Ok(providers)
 }
@@ -1,18 +1,19 @@
-use self::oracle::Price;
 use super::Provider;
+use crate::config::P2PProxyProviderConfig;
+use crate::config::SignatureScheme;
+use crate::http::build_client;
+use crate::proto::{encode_prices, oracle};
+use crate::round::{PriceRound, RoundPrice};
+use crate::signer::PriceSigner;
 use crate::Error;
-use crate::{config::P2PProxyProviderConfig, provider::MarketData};
 use async_trait::async_trait;
-use bigdecimal::ToPrimitive;
+use bigdecimal::{BigDecimal, ToPrimitive};
 use prost::Message;
 use reqwest::{Client, StatusCode};
 use serde::Serialize;
 
-pub mod oracle {
-    include!(concat!(env!("OUT_DIR"), "/oracle.rs"));
-}
-
 const P2P_PUBSUB_PUBLISH_URL: &str = "/pubsub/publish";
+const WAVES_PRICE_MULTIPLIER: i64 = 100_000_000;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct P2PRequest {
@@ -22,41 +23,39 @@ pub struct P2PRequest {
 
 #[derive(Debug, Clone)]
 pub struct P2PProvider {
+    // `p2p:{topic}@{endpoint}`
+    name: String,
     endpoint: String,
     topic: String,
     client: Client,
+    signer: Option<PriceSigner>,
 }
 
 impl P2PProvider {
-    pub fn new(config: &P2PProxyProviderConfig) -> Self {
-        P2PProvider {
+    pub fn new(config: &P2PProxyProviderConfig) -> Result<Self, Error> {
+        Ok(P2PProvider {
+            name: format!("p2p:{}@{}", config.topic, config.endpoint),
             endpoint: format!("{}{}", config.endpoint.clone(), P2P_PUBSUB_PUBLISH_URL),
             topic: config.topic.clone(),
-            client: Client::new(),
-        }
+            client: build_client(&config.http)?,
+            signer: config.signer.as_ref().map(PriceSigner::new).transpose()?,
+        })
     }
 }
 
 #[async_trait]
 impl Provider for P2PProvider {
-    async fn send(&self, prices: &[MarketData]) -> Result<(), Error> {
-        let mut binary_data = vec![];
-        oracle::Prices {
-            prices: prices
-                .iter()
-                .map(|data| {
-                    Ok(Price {
-                        ticker: data.ticker.clone(),
-                        price: data
-                            .price
-                            .to_f64()
-                            .ok_or_else(|| Error::Provider("can't encode price".to_string()))?,
-                        timestamp: data.timestamp,
-                    })
-                })
-                .collect::<Result<_, Error>>()?,
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, round: &PriceRound) -> Result<(), Error> {
+        let mut message = encode_prices(round)?;
+        if let Some(signer) = &self.signer {
+            sign(&mut message, round, signer).await?;
         }
-        .encode(&mut binary_data)?;
+        let mut binary_data = vec![];
+        message.encode(&mut binary_data)?;
 
         match self
             .client
@@ -77,3 +76,142 @@ impl Provider for P2PProvider {
         }
     }
 }
+
+// signs `payload` bytes as they are sent: waves canonical encoding or, for other
+// schemes, the message encoded once with empty `signature` and `payload`
+async fn sign(
+    message: &mut oracle::Prices,
+    round: &PriceRound,
+    signer: &PriceSigner,
+) -> Result<(), Error> {
+    message.domain = signer.domain().to_string();
+    message.scheme = match signer.scheme() {
+        SignatureScheme::Ed25519 => oracle::SignatureScheme::Ed25519,
+        SignatureScheme::Secp256k1 => oracle::SignatureScheme::Secp256k1,
+        SignatureScheme::Waves => oracle::SignatureScheme::Waves,
+    } as i32;
+    message.public_key = signer.public_key().await?;
+    message.key_id = signer.key_id().to_string();
+    message.signature = vec![];
+
+    message.payload = match signer.scheme() {
+        SignatureScheme::Waves => waves_payload(signer.domain(), round)?,
+        _ => message.encode_to_vec(),
+    };
+    message.signature = signer.sign(&message.payload).await?;
+    Ok(())
+}
+
+// canonical prices encoding readable with ride `toInt`/`toUtf8String`,
+// integers are 8 bytes big-endian: domain length, domain, round, prices count,
+// then ticker length, ticker, sequence, price * 10^8, timestamp of each price,
+// price is rounded half away from zero to 8 decimals
+fn waves_payload(domain: &str, round: &PriceRound) -> Result<Vec<u8>, Error> {
+    let mut payload = vec![];
+    put_string(&mut payload, domain);
+    payload.extend_from_slice(&round.id.to_be_bytes());
+    payload.extend_from_slice(&(round.prices.len() as i64).to_be_bytes());
+    for RoundPrice { sequence, data, .. } in &round.prices {
+        put_string(&mut payload, &data.ticker);
+        payload.extend_from_slice(&sequence.to_be_bytes());
+        let price = (&data.price * BigDecimal::from(WAVES_PRICE_MULTIPLIER))
+            .round(0)
+            .to_i64()
+            .ok_or_else(|| Error::Provider("can't encode price".to_string()))?;
+        payload.extend_from_slice(&price.to_be_bytes());
+        payload.extend_from_slice(&data.timestamp.to_be_bytes());
+    }
+    Ok(payload)
+}
+
+fn put_string(payload: &mut Vec<u8>, value: &str) {
+    payload.extend_from_slice(&(value.len() as i64).to_be_bytes());
+    payload.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign, waves_payload};
+    use crate::config::{SignatureScheme, SignerConfig};
+    use crate::fixtures::{market_data, ED25519_KEY};
+    use crate::proto::{encode_prices, oracle};
+    use crate::round::{PriceRound, RoundPrice};
+    use crate::signer::PriceSigner;
+    use bigdecimal::BigDecimal;
+    use ed25519_dalek::{PublicKey, Signature, Verifier};
+    use prost::Message;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn round() -> PriceRound {
+        PriceRound {
+            id: 7,
+            instance_id: "oracle".to_string(),
+            prices: vec![RoundPrice {
+                sequence: 5,
+                sources: vec!["binance".to_string()],
+                data: market_data(
+                    "binance",
+                    "BTC",
+                    BigDecimal::from_str("1.23456789").unwrap(),
+                    BigDecimal::from(1),
+                    1650000000,
+                ),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn signed_payload() {
+        let signer = PriceSigner::new(&SignerConfig {
+            scheme: SignatureScheme::Ed25519,
+            key: Some(ED25519_KEY.to_string()),
+            key_file: None,
+            key_env: None,
+            password_env: None,
+            remote: None,
+            key_id: "oracle".to_string(),
+            domain: "price-oracle".to_string(),
+        })
+        .unwrap();
+        let round = round();
+        let mut message = encode_prices(&round).unwrap();
+        sign(&mut message, &round, &signer).await.unwrap();
+
+        // consumer verifies payload bytes, then decodes them
+        let message = oracle::Prices::decode(message.encode_to_vec().as_slice()).unwrap();
+        let public_key = PublicKey::from_bytes(&message.public_key).unwrap();
+        let signature = Signature::try_from(message.signature.as_slice()).unwrap();
+        assert!(public_key.verify(&message.payload, &signature).is_ok());
+
+        let signed = oracle::Prices::decode(message.payload.as_slice()).unwrap();
+        assert_eq!(signed.prices, message.prices);
+        assert_eq!(signed.round, 7);
+        assert_eq!(signed.domain, "price-oracle");
+        assert_eq!(signed.key_id, "oracle");
+        assert!(signed.signature.is_empty());
+    }
+
+    #[test]
+    fn payload() {
+        let round = round();
+        let mut expected = vec![];
+        expected.extend_from_slice(&2i64.to_be_bytes());
+        expected.extend_from_slice(b"po");
+        expected.extend_from_slice(&7u64.to_be_bytes());
+        expected.extend_from_slice(&1i64.to_be_bytes());
+        expected.extend_from_slice(&3i64.to_be_bytes());
+        expected.extend_from_slice(b"BTC");
+        expected.extend_from_slice(&5u64.to_be_bytes());
+        expected.extend_from_slice(&123456789i64.to_be_bytes());
+        expected.extend_from_slice(&1650000000i64.to_be_bytes());
+        assert_eq!(waves_payload("po", &round).unwrap(), expected);
+
+        let mut round = round;
+        let price = |round: &PriceRound| waves_payload("po", round).unwrap()[45..53].to_vec();
+        round.prices[0].data.price = BigDecimal::from_str("1.234567895").unwrap();
+        assert_eq!(price(&round), 123456790i64.to_be_bytes());
+        round.prices[0].data.price = BigDecimal::from_str("1.2345678949").unwrap();
+        assert_eq!(price(&round), 123456789i64.to_be_bytes());
+    }
+}
@@ -0,0 +1,290 @@
+use super::Provider;
+use crate::config::WebSocketProviderConfig;
+use crate::error::Error;
+use crate::proto::encode_price;
+use crate::round::{PriceRound, RoundPrice};
+use async_trait::async_trait;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Extension, Query};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use bigdecimal::BigDecimal;
+use pepe_log::{error, info};
+use prost::Message as _;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+const ALL_TICKERS: &str = "*";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    // text `PriceUpdate` messages
+    #[default]
+    Json,
+    // binary `oracle.Price` messages
+    Protobuf,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Params {
+    #[serde(default)]
+    format: Format,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Subscribe,
+    Unsubscribe,
+}
+
+// `{"action":"subscribe","tickers":["BTC"]}`, `*` ticker stands for all tickers
+#[derive(Debug, Deserialize)]
+struct ClientMessage {
+    action: Action,
+    tickers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ServerMessage<'a> {
+    Subscriptions(Vec<&'a String>),
+    Error(String),
+}
+
+#[derive(Debug, Serialize)]
+struct PriceUpdate<'a> {
+    round: u64,
+    sequence: u64,
+    ticker: &'a str,
+    price: BigDecimal,
+    volume: BigDecimal,
+    timestamp: i64,
+    sources: &'a [String],
+}
+
+// pushes published rounds to websocket clients subscribed to tickers,
+// every client has own queue, so slow clients don't stall publishing
+#[derive(Debug)]
+pub struct WebSocketProvider {
+    rounds: broadcast::Sender<Arc<PriceRound>>,
+}
+
+impl WebSocketProvider {
+    pub fn new(config: &WebSocketProviderConfig) -> Result<Self, Error> {
+        let address: SocketAddr = config
+            .address
+            .parse()
+            .map_err(|e| Error::HttpServer(format!("invalid websocket address: {}", e)))?;
+        let (rounds, _) = broadcast::channel(config.buffer.max(1));
+
+        let server = axum::Server::try_bind(&address)
+            .map_err(|e| Error::HttpServer(e.to_string()))?
+            .serve(router(rounds.clone()).into_make_service());
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                error!("websocket server stopped: {}", e);
+            }
+        });
+
+        Ok(WebSocketProvider { rounds })
+    }
+}
+
+#[async_trait]
+impl Provider for WebSocketProvider {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    async fn send(&self, round: &PriceRound) -> Result<(), Error> {
+        // fails only if there are no clients
+        let _ = self.rounds.send(Arc::new(round.clone()));
+        Ok(())
+    }
+}
+
+// `GET /ws?format=json|protobuf`
+fn router(rounds: broadcast::Sender<Arc<PriceRound>>) -> Router {
+    Router::new()
+        .route("/ws", get(upgrade))
+        .layer(Extension(rounds))
+}
+
+async fn upgrade(
+    ws: WebSocketUpgrade,
+    Query(params): Query<Params>,
+    Extension(rounds): Extension<broadcast::Sender<Arc<PriceRound>>>,
+) -> Response {
+    let rounds = rounds.subscribe();
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = serve_client(socket, rounds, params.format).await {
+            info!("websocket client disconnected: {}", e);
+        }
+    })
+}
+
+async fn serve_client(
+    mut socket: WebSocket,
+    mut rounds: broadcast::Receiver<Arc<PriceRound>>,
+    format: Format,
+) -> Result<(), Error> {
+    let mut tickers = HashSet::new();
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(Error::HttpServer(e.to_string())),
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(message) => {
+                        match message.action {
+                            Action::Subscribe => tickers.extend(message.tickers),
+                            Action::Unsubscribe => {
+                                for ticker in &message.tickers {
+                                    tickers.remove(ticker);
+                                }
+                            }
+                        };
+                        let mut subscriptions = tickers.iter().collect::<Vec<_>>();
+                        subscriptions.sort();
+                        serde_json::to_string(&ServerMessage::Subscriptions(subscriptions))?
+                    }
+                    Err(e) => serde_json::to_string(&ServerMessage::Error(e.to_string()))?,
+                };
+                send(&mut socket, Message::Text(reply)).await?;
+            }
+            round = rounds.recv() => {
+                let round = match round {
+                    Ok(round) => round,
+                    Err(RecvError::Lagged(skipped)) => {
+                        info!("slow websocket client skipped {} rounds", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+                for price in round
+                    .prices
+                    .iter()
+                    .filter(|price| tickers.contains(ALL_TICKERS) || tickers.contains(&price.data.ticker))
+                {
+                    send(&mut socket, encode(&round, price, format)?).await?;
+                }
+            }
+        }
+    }
+}
+
+fn encode(round: &PriceRound, price: &RoundPrice, format: Format) -> Result<Message, Error> {
+    Ok(match format {
+        Format::Json => Message::Text(serde_json::to_string(&PriceUpdate {
+            round: round.id,
+            sequence: price.sequence,
+            ticker: &price.data.ticker,
+            price: price.data.price.normalized(),
+            volume: price.data.volume.normalized(),
+            timestamp: price.data.timestamp,
+            sources: &price.sources,
+        })?),
+        Format::Protobuf => Message::Binary(encode_price(price)?.encode_to_vec()),
+    })
+}
+
+async fn send(socket: &mut WebSocket, message: Message) -> Result<(), Error> {
+    socket
+        .send(message)
+        .await
+        .map_err(|e| Error::HttpServer(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::router;
+    use crate::fixtures;
+    use crate::proto::oracle;
+    use crate::round::PriceRound;
+    use futures::{SinkExt, StreamExt};
+    use prost::Message as _;
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn round(id: u64, tickers: &[&str]) -> Arc<PriceRound> {
+        Arc::new(fixtures::round(id, tickers))
+    }
+
+    #[tokio::test]
+    async fn push() {
+        let (rounds, _) = broadcast::channel(1);
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(router(rounds.clone()).into_make_service());
+        let endpoint = format!("ws://{}/ws", server.local_addr());
+        tokio::spawn(server);
+
+        let (mut json_client, _) = tokio_tungstenite::connect_async(&endpoint).await.unwrap();
+        let (mut proto_client, _) =
+            tokio_tungstenite::connect_async(format!("{}?format=protobuf", endpoint))
+                .await
+                .unwrap();
+        for client in [&mut json_client, &mut proto_client] {
+            client
+                .send(Message::Text(
+                    json!({"action": "subscribe", "tickers": ["ETH"]}).to_string(),
+                ))
+                .await
+                .unwrap();
+            let reply = client.next().await.unwrap().unwrap().into_text().unwrap();
+            assert_eq!(
+                serde_json::from_str::<Value>(&reply).unwrap(),
+                json!({"subscriptions": ["ETH"]})
+            );
+        }
+
+        rounds.send(round(1, &["BTC", "ETH"])).unwrap();
+        let update = json_client
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_text()
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&update).unwrap(),
+            json!({
+                "round": 1,
+                "sequence": 1,
+                "ticker": "ETH",
+                "price": "42",
+                "volume": "1",
+                "timestamp": 0,
+                "sources": ["binance"],
+            })
+        );
+        let update = proto_client.next().await.unwrap().unwrap().into_data();
+        let price = oracle::Price::decode(update.as_slice()).unwrap();
+        assert_eq!(price.ticker, "ETH");
+        assert_eq!(price.price_decimal, "42");
+
+        // slow client skips rounds it can't keep up with
+        rounds.send(round(2, &["ETH"])).unwrap();
+        rounds.send(round(3, &["ETH"])).unwrap();
+        let update = json_client
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_text()
+            .unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&update).unwrap()["round"], 3);
+    }
+}
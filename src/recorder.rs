@@ -0,0 +1,210 @@
+use crate::collector::MarketData;
+use crate::config::RecorderConfig;
+use crate::error::Error;
+use chrono::Utc;
+use pepe_log::error;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+const RECORDER_FILE_PREFIX: &str = "market-data-";
+const RECORDER_FILE_EXTENSION: &str = ".jsonl";
+// market data waiting to be written, more is dropped
+const RECORDER_QUEUE_SIZE: usize = 1024;
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    #[serde(flatten)]
+    data: &'a MarketData,
+    received_at_ms: i64,
+}
+
+// queue of market data written by recorder task
+pub struct RecorderQueue {
+    tx: mpsc::Sender<(MarketData, i64)>,
+}
+
+impl RecorderQueue {
+    // never waits for disk, market data is dropped if recorder can't keep up
+    pub fn record(&self, market_data: &MarketData) -> Result<(), Error> {
+        self.tx
+            .try_send((market_data.clone(), Utc::now().timestamp_millis()))
+            .map_err(|e| {
+                Error::IO(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!("recorder queue: {}", e),
+                ))
+            })
+    }
+}
+
+// writes received market data to rotating jsonl files readable by replay collector
+pub struct MarketDataRecorder {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    size: u64,
+    sequence: u64,
+    writer: Option<BufWriter<File>>,
+}
+
+impl MarketDataRecorder {
+    pub fn new(config: &RecorderConfig) -> Result<Self, Error> {
+        if config.max_files == 0 {
+            return Err(Error::ConfigLoad(config::ConfigError::Message(
+                "recorder max_files must be at least 1".to_string(),
+            )));
+        }
+        fs::create_dir_all(&config.path)?;
+        Ok(MarketDataRecorder {
+            path: PathBuf::from(&config.path),
+            max_size: config.max_size,
+            max_files: config.max_files,
+            size: 0,
+            sequence: 0,
+            writer: None,
+        })
+    }
+
+    // moves recorder to blocking task, so disk io doesn't stall market data consumer,
+    // written data is flushed whenever the queue is drained
+    pub fn spawn(mut self) -> RecorderQueue {
+        let (tx, mut rx) = mpsc::channel(RECORDER_QUEUE_SIZE);
+        tokio::task::spawn_blocking(move || {
+            while let Some((market_data, received_at_ms)) = rx.blocking_recv() {
+                if let Err(e) = self.record(&market_data, received_at_ms) {
+                    error!("can't record market data: {}", e);
+                }
+                if rx.is_empty() {
+                    if let Err(e) = self.flush() {
+                        error!("can't flush recorded market data: {}", e);
+                    }
+                }
+            }
+        });
+        RecorderQueue { tx }
+    }
+
+    fn record(&mut self, market_data: &MarketData, received_at_ms: i64) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(&Record {
+            data: market_data,
+            received_at_ms,
+        })?;
+        line.push(b'\n');
+
+        if self.writer.is_none() || self.size >= self.max_size {
+            self.rotate()?;
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(&line)?;
+            self.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    // open new file and remove the oldest ones over max files
+    fn rotate(&mut self) -> Result<(), Error> {
+        let file_path = self.path.join(format!(
+            "{}{}-{:06}{}",
+            RECORDER_FILE_PREFIX,
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            self.sequence,
+            RECORDER_FILE_EXTENSION
+        ));
+        self.sequence += 1;
+        self.flush()?;
+        self.writer = Some(BufWriter::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file_path)?,
+        ));
+        self.size = 0;
+
+        let mut files = fs::read_dir(&self.path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| {
+                        name.starts_with(RECORDER_FILE_PREFIX)
+                            && name.ends_with(RECORDER_FILE_EXTENSION)
+                    })
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        // max files is at least 1, so the current file is kept
+        if files.len() > self.max_files {
+            for file in &files[..files.len() - self.max_files] {
+                fs::remove_file(file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MarketDataRecorder;
+    use crate::collector::MarketData;
+    use crate::config::RecorderConfig;
+    use crate::fixtures;
+    use bigdecimal::BigDecimal;
+    use std::fs;
+
+    #[test]
+    fn max_files() {
+        assert!(MarketDataRecorder::new(&RecorderConfig {
+            enabled: true,
+            path: std::env::temp_dir().to_string_lossy().to_string(),
+            max_size: 1,
+            max_files: 0,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn rotate() {
+        let path =
+            std::env::temp_dir().join(format!("price-oracle-recorder-{}", std::process::id()));
+        let mut recorder = MarketDataRecorder::new(&RecorderConfig {
+            enabled: true,
+            path: path.to_string_lossy().to_string(),
+            max_size: 1,
+            max_files: 2,
+        })
+        .unwrap();
+        let mut market_data = fixtures::market_data(
+            "binance",
+            "BTC",
+            BigDecimal::from(40000),
+            BigDecimal::from(10),
+            1647561600,
+        );
+        market_data.latency_ms = Some(120);
+
+        for _ in 0..3 {
+            recorder.record(&market_data, 0).unwrap();
+        }
+        recorder.flush().unwrap();
+
+        let files = fs::read_dir(&path).unwrap().collect::<Vec<_>>();
+        assert_eq!(files.len(), 2);
+        let content = fs::read_to_string(files[0].as_ref().unwrap().path()).unwrap();
+        assert!(content.contains("received_at_ms"));
+        let recorded: MarketData = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(recorded, market_data);
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
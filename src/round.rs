@@ -0,0 +1,163 @@
+use crate::aggregator::AggregatedPrice;
+use crate::collector::MarketData;
+use crate::error::Error;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+// aggregated prices published by providers at once
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceRound {
+    pub id: u64,
+    // oracle instance published the round
+    pub instance_id: String,
+    pub prices: Vec<RoundPrice>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundPrice {
+    // per ticker sequence number
+    pub sequence: u64,
+    pub data: MarketData,
+    // providers the price is aggregated from
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct RoundState {
+    round: u64,
+    sequences: HashMap<String, u64>,
+}
+
+// numbers aggregation rounds and ticker prices, numbers never repeat:
+// with state file they continue from persisted values,
+// without it they start from startup unix time in milliseconds
+pub struct RoundSequencer {
+    instance_id: String,
+    path: Option<PathBuf>,
+    seed: u64,
+    state: RoundState,
+}
+
+impl RoundSequencer {
+    pub fn new(instance_id: &str, path: Option<&str>) -> Result<Self, Error> {
+        let instance_id = instance_id.to_string();
+        Ok(match path {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                let state = if path.exists() {
+                    serde_json::from_slice(&fs::read(&path)?)?
+                } else {
+                    RoundState::default()
+                };
+                RoundSequencer {
+                    instance_id,
+                    path: Some(path),
+                    seed: 0,
+                    state,
+                }
+            }
+            None => RoundSequencer {
+                instance_id,
+                path: None,
+                seed: Utc::now().timestamp_millis() as u64,
+                state: RoundState::default(),
+            },
+        })
+    }
+
+    // numbers next round, state is persisted before round is published
+    pub fn next(&mut self, prices: Vec<AggregatedPrice>) -> Result<PriceRound, Error> {
+        let seed = self.seed;
+        self.state.round = self.state.round.max(seed) + 1;
+        let prices = prices
+            .into_iter()
+            .map(|AggregatedPrice { data, sources }| {
+                let sequence = self.state.sequences.entry(data.ticker.clone()).or_default();
+                *sequence = (*sequence).max(seed) + 1;
+                RoundPrice {
+                    sequence: *sequence,
+                    data,
+                    sources,
+                }
+            })
+            .collect();
+        self.save()?;
+
+        Ok(PriceRound {
+            id: self.state.round,
+            instance_id: self.instance_id.clone(),
+            prices,
+        })
+    }
+
+    // written to temporary file first, so crash doesn't leave partial state
+    fn save(&self) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, serde_json::to_vec(&self.state)?)?;
+            fs::rename(&tmp_path, path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundSequencer;
+    use crate::aggregator::AggregatedPrice;
+    use crate::fixtures::market_data;
+    use bigdecimal::BigDecimal;
+
+    fn price(ticker: &str) -> AggregatedPrice {
+        let data = market_data(
+            "binance",
+            ticker,
+            BigDecimal::from(1),
+            BigDecimal::from(1),
+            0,
+        );
+        AggregatedPrice {
+            data,
+            sources: vec!["binance".to_string()],
+        }
+    }
+
+    fn sequences(sequencer: &mut RoundSequencer, tickers: &[&str]) -> (u64, Vec<u64>) {
+        let round = sequencer
+            .next(tickers.iter().map(|ticker| price(ticker)).collect())
+            .unwrap();
+        (
+            round.id,
+            round.prices.iter().map(|price| price.sequence).collect(),
+        )
+    }
+
+    #[test]
+    fn persisted() {
+        let path = std::env::temp_dir().join(format!(
+            "price-oracle-rounds-{}.json",
+            rand::random::<u64>()
+        ));
+        let path = path.to_str().unwrap();
+
+        let mut sequencer = RoundSequencer::new("oracle", Some(path)).unwrap();
+        assert_eq!(sequences(&mut sequencer, &["BTC", "ETH"]), (1, vec![1, 1]));
+        assert_eq!(sequences(&mut sequencer, &["BTC"]), (2, vec![2]));
+
+        // restart
+        let mut sequencer = RoundSequencer::new("oracle", Some(path)).unwrap();
+        assert_eq!(sequences(&mut sequencer, &["BTC", "ETH"]), (3, vec![3, 2]));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn seeded() {
+        let mut sequencer = RoundSequencer::new("oracle", None).unwrap();
+        let (round, sequences) = sequences(&mut sequencer, &["BTC"]);
+        assert_eq!(round, sequencer.seed + 1);
+        assert_eq!(sequences, vec![sequencer.seed + 1]);
+    }
+}
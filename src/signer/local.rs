@@ -0,0 +1,110 @@
+use super::waves::WavesKey;
+use super::Signer;
+use crate::config::SignatureScheme;
+use crate::error::Error;
+use async_trait::async_trait;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+
+// in-process key signer
+pub enum LocalSigner {
+    Ed25519(Keypair),
+    Secp256k1(k256::ecdsa::SigningKey),
+    Waves(WavesKey),
+}
+
+impl LocalSigner {
+    // hex encoded key, base58 for waves scheme
+    pub fn decode(scheme: SignatureScheme, key: &str) -> Result<Self, Error> {
+        let secret = match scheme {
+            SignatureScheme::Waves => bs58::decode(key.trim())
+                .into_vec()
+                .map_err(|e| Error::Signer(format!("can't decode private key: {}", e)))?,
+            _ => hex::decode(key.trim().trim_start_matches("0x"))
+                .map_err(|e| Error::Signer(format!("can't decode private key: {}", e)))?,
+        };
+        Self::from_bytes(scheme, &secret)
+    }
+
+    pub fn from_bytes(scheme: SignatureScheme, secret: &[u8]) -> Result<Self, Error> {
+        Ok(match scheme {
+            SignatureScheme::Ed25519 => {
+                let secret = SecretKey::from_bytes(secret)
+                    .map_err(|e| Error::Signer(format!("invalid ed25519 key: {}", e)))?;
+                let public = PublicKey::from(&secret);
+                LocalSigner::Ed25519(Keypair { secret, public })
+            }
+            SignatureScheme::Secp256k1 => LocalSigner::Secp256k1(
+                k256::ecdsa::SigningKey::from_bytes(secret)
+                    .map_err(|e| Error::Signer(format!("invalid secp256k1 key: {}", e)))?,
+            ),
+            SignatureScheme::Waves => LocalSigner::Waves(WavesKey::from_bytes(secret)?),
+        })
+    }
+
+    // ed25519 32 bytes key, secp256k1 33 bytes compressed sec1 key,
+    // waves 32 bytes curve25519 account key
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match self {
+            LocalSigner::Ed25519(keypair) => keypair.public.to_bytes().to_vec(),
+            LocalSigner::Secp256k1(key) => key.verifying_key().to_bytes().to_vec(),
+            LocalSigner::Waves(key) => key.public_key().to_vec(),
+        }
+    }
+
+    // 64 bytes signature, secp256k1 one is `r || s`
+    pub fn sign_bytes(&self, message: &[u8]) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+
+        match self {
+            LocalSigner::Ed25519(keypair) => keypair.sign(message).to_bytes().to_vec(),
+            LocalSigner::Secp256k1(key) => {
+                let signature: k256::ecdsa::Signature = key.sign(message);
+                signature.as_ref().to_vec()
+            }
+            LocalSigner::Waves(key) => key.sign(message).to_vec(),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for LocalSigner {
+    async fn public_key(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.public_key_bytes())
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.sign_bytes(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalSigner;
+    use crate::config::SignatureScheme;
+    use crate::fixtures::ED25519_KEY;
+    use ed25519_dalek::Verifier;
+
+    #[test]
+    fn ed25519() {
+        let signer = LocalSigner::decode(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+        let public_key = ed25519_dalek::PublicKey::from_bytes(&signer.public_key_bytes()).unwrap();
+        let signature =
+            ed25519_dalek::Signature::from_bytes(&signer.sign_bytes(b"prices")).unwrap();
+        assert!(public_key.verify(b"prices", &signature).is_ok());
+        assert!(public_key.verify(b"other", &signature).is_err());
+    }
+
+    #[test]
+    fn secp256k1() {
+        use k256::ecdsa::signature::Verifier;
+        use std::convert::TryFrom;
+
+        let signer = LocalSigner::decode(SignatureScheme::Secp256k1, ED25519_KEY).unwrap();
+        let public_key =
+            k256::ecdsa::VerifyingKey::from_sec1_bytes(&signer.public_key_bytes()).unwrap();
+        let signature =
+            k256::ecdsa::Signature::try_from(signer.sign_bytes(b"prices").as_slice()).unwrap();
+        assert!(public_key.verify(b"prices", &signature).is_ok());
+        assert!(public_key.verify(b"other", &signature).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use self::local::LocalSigner;
+use self::remote::RemoteSigner;
+use crate::config::{SignatureScheme, SignerConfig};
+use crate::error::Error;
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+
+mod local;
+mod remote;
+mod waves;
+
+#[async_trait]
+pub trait Signer: Send + Sync {
+    async fn public_key(&self) -> Result<Vec<u8>, Error>;
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+pub fn init_signer(config: &SignerConfig) -> Result<Arc<dyn Signer>, Error> {
+    match (
+        &config.key,
+        &config.key_file,
+        &config.key_env,
+        &config.remote,
+    ) {
+        (Some(key), None, None, None) => Ok(Arc::new(LocalSigner::decode(config.scheme, key)?)),
+        (None, Some(path), None, None) => Ok(Arc::new(load_key_file(config, path)?)),
+        (None, None, Some(name), None) => Ok(Arc::new(LocalSigner::decode(
+            config.scheme,
+            &read_env(name)?,
+        )?)),
+        (None, None, None, Some(remote)) => Ok(Arc::new(RemoteSigner::new(
+            remote,
+            config.scheme,
+            &config.key_id,
+        )?)),
+        _ => Err(Error::Signer(
+            "exactly one of key, key_file, key_env or remote is expected".to_string(),
+        )),
+    }
+}
+
+// encoded key file or encrypted json keystore
+fn load_key_file(config: &SignerConfig, path: &str) -> Result<LocalSigner, Error> {
+    let content = std::fs::read_to_string(path)?;
+    if !content.trim_start().starts_with('{') {
+        return LocalSigner::decode(config.scheme, &content);
+    }
+
+    let password = match &config.password_env {
+        Some(name) => read_env(name)?,
+        None => {
+            return Err(Error::Signer(
+                "password_env is expected for keystore".to_string(),
+            ))
+        }
+    };
+    let secret = eth_keystore::decrypt_key(path, password)
+        .map_err(|e| Error::Signer(format!("can't decrypt keystore: {}", e)))?;
+    LocalSigner::from_bytes(config.scheme, &secret)
+}
+
+fn read_env(name: &str) -> Result<String, Error> {
+    std::env::var(name).map_err(|e| Error::Signer(format!("can't read env {}: {}", name, e)))
+}
+
+// signs published price messages with configured signer
+#[derive(Clone)]
+pub struct PriceSigner {
+    signer: Arc<dyn Signer>,
+    scheme: SignatureScheme,
+    key_id: String,
+    domain: String,
+}
+
+impl PriceSigner {
+    pub fn new(config: &SignerConfig) -> Result<Self, Error> {
+        Ok(PriceSigner {
+            signer: init_signer(config)?,
+            scheme: config.scheme,
+            key_id: config.key_id.clone(),
+            domain: config.domain.clone(),
+        })
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub async fn public_key(&self) -> Result<Vec<u8>, Error> {
+        self.signer.public_key().await
+    }
+
+    pub async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        self.signer.sign(message).await
+    }
+}
+
+impl fmt::Debug for PriceSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriceSigner")
+            .field("scheme", &self.scheme)
+            .field("key_id", &self.key_id)
+            .field("domain", &self.domain)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::init_signer;
+    use crate::config::{SignatureScheme, SignerConfig};
+    use crate::fixtures::{ED25519_KEY, ED25519_PUBLIC_KEY};
+
+    fn config() -> SignerConfig {
+        SignerConfig {
+            scheme: SignatureScheme::Ed25519,
+            key: None,
+            key_file: None,
+            key_env: None,
+            password_env: None,
+            remote: None,
+            key_id: "oracle".to_string(),
+            domain: "price-oracle".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn key_sources() {
+        assert!(init_signer(&config()).is_err());
+
+        let mut config = config();
+        config.key = Some(ED25519_KEY.to_string());
+        let public_key = init_signer(&config).unwrap().public_key().await.unwrap();
+        assert_eq!(hex::encode(public_key), ED25519_PUBLIC_KEY);
+
+        config.key = None;
+        std::env::set_var("PRICE_ORACLE_TEST_KEY", ED25519_KEY);
+        config.key_env = Some("PRICE_ORACLE_TEST_KEY".to_string());
+        let public_key = init_signer(&config).unwrap().public_key().await.unwrap();
+        assert_eq!(hex::encode(public_key), ED25519_PUBLIC_KEY);
+
+        config.key_env = None;
+        let dir =
+            std::env::temp_dir().join(format!("price-oracle-keystore-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        eth_keystore::encrypt_key(
+            &dir,
+            &mut rand::thread_rng(),
+            hex::decode(ED25519_KEY).unwrap(),
+            "secret",
+            Some("oracle.json"),
+        )
+        .unwrap();
+        config.key_file = Some(dir.join("oracle.json").to_string_lossy().to_string());
+        assert!(init_signer(&config).is_err());
+
+        std::env::set_var("PRICE_ORACLE_TEST_PASSWORD", "secret");
+        config.password_env = Some("PRICE_ORACLE_TEST_PASSWORD".to_string());
+        let public_key = init_signer(&config).unwrap().public_key().await.unwrap();
+        assert_eq!(hex::encode(public_key), ED25519_PUBLIC_KEY);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
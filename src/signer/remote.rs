@@ -0,0 +1,248 @@
+use super::Signer;
+use crate::config::{RemoteSignerConfig, SignatureScheme};
+use crate::error::Error;
+use crate::http::build_client;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct KeyResponse {
+    scheme: SignatureScheme,
+    // base64 encoded
+    public_key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SignRequest {
+    // base64 encoded
+    message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SignResponse {
+    // base64 encoded
+    signature: String,
+    // base64 encoded key the message is signed with, optional
+    #[serde(default)]
+    public_key: Option<String>,
+}
+
+// signer service holding the keys:
+// `GET {endpoint}/keys/{key_id}` returns key scheme and public key,
+// `POST {endpoint}/keys/{key_id}/sign` signs message
+#[derive(Debug)]
+pub struct RemoteSigner {
+    endpoint: String,
+    scheme: SignatureScheme,
+    client: Client,
+    key_ttl: Duration,
+    // public key and time it was requested at
+    public_key: RwLock<Option<(Vec<u8>, Instant)>>,
+}
+
+impl RemoteSigner {
+    pub fn new(
+        config: &RemoteSignerConfig,
+        scheme: SignatureScheme,
+        key_id: &str,
+    ) -> Result<Self, Error> {
+        Ok(RemoteSigner {
+            endpoint: format!("{}/keys/{}", config.endpoint.trim_end_matches('/'), key_id),
+            scheme,
+            client: build_client(&config.http)?,
+            key_ttl: config.key_ttl.into(),
+            public_key: RwLock::new(None),
+        })
+    }
+
+    async fn get_public_key(&self) -> Result<Vec<u8>, Error> {
+        let res = self.client.get(&self.endpoint).send().await?;
+        if res.status() != StatusCode::OK {
+            return Err(Error::Signer(format!(
+                "can't get public key from remote signer, status code: {}",
+                res.status()
+            )));
+        }
+        let key: KeyResponse = res.json().await?;
+        if key.scheme != self.scheme {
+            return Err(Error::Signer(format!(
+                "remote signer key scheme mismatch: {:?}",
+                key.scheme
+            )));
+        }
+        decode_base64(&key.public_key)
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    // cached public key, requested again once `key_ttl` passes
+    async fn public_key(&self) -> Result<Vec<u8>, Error> {
+        if let Some((public_key, requested_at)) = &*self.public_key.read().await {
+            if requested_at.elapsed() < self.key_ttl {
+                return Ok(public_key.clone());
+            }
+        }
+        let public_key = self.get_public_key().await?;
+        *self.public_key.write().await = Some((public_key.clone(), Instant::now()));
+        Ok(public_key)
+    }
+
+    // fails if signer reports a key other than the cached one, e.g. rotated,
+    // so signature is never published with stale public key, the new key is cached
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let res = self
+            .client
+            .post(format!("{}/sign", self.endpoint))
+            .json(&SignRequest {
+                message: base64::encode(message),
+            })
+            .send()
+            .await?;
+        if res.status() != StatusCode::OK {
+            return Err(Error::Signer(format!(
+                "can't sign with remote signer, status code: {}",
+                res.status()
+            )));
+        }
+        let signature: SignResponse = res.json().await?;
+        if let Some(public_key) = &signature.public_key {
+            let public_key = decode_base64(public_key)?;
+            let mut cached = self.public_key.write().await;
+            if cached.as_ref().map(|(cached, _)| cached) != Some(&public_key) {
+                *cached = Some((public_key, Instant::now()));
+                return Err(Error::Signer(
+                    "remote signer key changed, message is signed with other key".to_string(),
+                ));
+            }
+        }
+        decode_base64(&signature.signature)
+    }
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, Error> {
+    base64::decode(value).map_err(|e| Error::Signer(format!("can't decode base64: {}", e)))
+}
+
+// mock signer service backed by local key
+#[cfg(test)]
+mod mock {
+    use super::{KeyResponse, SignRequest, SignResponse};
+    use crate::config::SignatureScheme;
+    use crate::signer::local::LocalSigner;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server, StatusCode};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    // serves encoded `key` as `key_id` on random local port, returns endpoint
+    pub fn serve(key_id: &str, scheme: SignatureScheme, key: &str) -> String {
+        let signer = Arc::new((scheme, LocalSigner::decode(scheme, key).unwrap()));
+        let key_path = format!("/keys/{}", key_id);
+        let make_service = make_service_fn(move |_| {
+            let signer = signer.clone();
+            let key_path = key_path.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    handle(req, signer.clone(), key_path.clone())
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let endpoint = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        endpoint
+    }
+
+    async fn handle(
+        req: Request<Body>,
+        signer: Arc<(SignatureScheme, LocalSigner)>,
+        key_path: String,
+    ) -> Result<Response<Body>, Infallible> {
+        let (scheme, signer) = signer.as_ref();
+        let sign_path = format!("{}/sign", key_path);
+        let body = match (req.method(), req.uri().path()) {
+            (&Method::GET, path) if path == key_path => serde_json::to_vec(&KeyResponse {
+                scheme: *scheme,
+                public_key: base64::encode(signer.public_key_bytes()),
+            }),
+            (&Method::POST, path) if path == sign_path => {
+                let body = hyper::body::to_bytes(req.into_body())
+                    .await
+                    .unwrap_or_default();
+                match serde_json::from_slice::<SignRequest>(&body)
+                    .ok()
+                    .and_then(|request| base64::decode(request.message).ok())
+                {
+                    Some(message) => serde_json::to_vec(&SignResponse {
+                        signature: base64::encode(signer.sign_bytes(&message)),
+                        public_key: Some(base64::encode(signer.public_key_bytes())),
+                    }),
+                    None => return Ok(status(StatusCode::BAD_REQUEST)),
+                }
+            }
+            _ => return Ok(status(StatusCode::NOT_FOUND)),
+        };
+        Ok(match body {
+            Ok(body) => Response::new(Body::from(body)),
+            Err(_) => status(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+    }
+
+    fn status(status: StatusCode) -> Response<Body> {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = status;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mock, RemoteSigner};
+    use crate::config::{RemoteSignerConfig, SignatureScheme};
+    use crate::fixtures::ED25519_KEY;
+    use crate::signer::Signer;
+    use ed25519_dalek::Verifier;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[tokio::test]
+    async fn remote_sign() {
+        let endpoint = mock::serve("oracle", SignatureScheme::Ed25519, ED25519_KEY);
+        let config = RemoteSignerConfig {
+            endpoint,
+            http: Default::default(),
+            key_ttl: Duration::from_secs(60).into(),
+        };
+
+        let signer = RemoteSigner::new(&config, SignatureScheme::Ed25519, "oracle").unwrap();
+        let public_key =
+            ed25519_dalek::PublicKey::from_bytes(&signer.public_key().await.unwrap()).unwrap();
+        let signature =
+            ed25519_dalek::Signature::from_bytes(&signer.sign(b"prices").await.unwrap()).unwrap();
+        assert!(public_key.verify(b"prices", &signature).is_ok());
+
+        // rotated key is reported by sign response and cached
+        *signer.public_key.write().await = Some((vec![0; 32], Instant::now()));
+        assert!(signer.sign(b"prices").await.is_err());
+        assert_eq!(signer.public_key().await.unwrap(), public_key.to_bytes());
+        assert!(signer.sign(b"prices").await.is_ok());
+
+        // expired key is requested again
+        let mut config = config;
+        config.key_ttl = Duration::ZERO.into();
+        let signer = RemoteSigner::new(&config, SignatureScheme::Ed25519, "oracle").unwrap();
+        *signer.public_key.write().await = Some((vec![0; 32], Instant::now()));
+        assert_eq!(signer.public_key().await.unwrap(), public_key.to_bytes());
+
+        let signer = RemoteSigner::new(&config, SignatureScheme::Secp256k1, "oracle").unwrap();
+        assert!(signer.public_key().await.is_err());
+        let signer = RemoteSigner::new(&config, SignatureScheme::Ed25519, "unknown").unwrap();
+        assert!(signer.sign(b"prices").await.is_err());
+    }
+}
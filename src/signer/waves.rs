@@ -0,0 +1,124 @@
+use crate::error::Error;
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+use rand::Rng;
+use sha2::{Digest, Sha512};
+
+const WAVES_KEY_LENGTH: usize = 32;
+const WAVES_SIGNATURE_LENGTH: usize = 64;
+const SIGN_BIT: u8 = 0x80;
+
+// waves curve25519 key, signatures are ed25519 ones made with x25519 private key
+// and edwards public key sign bit kept in the last signature byte
+pub struct WavesKey {
+    secret: [u8; WAVES_KEY_LENGTH],
+    scalar: Scalar,
+    // edwards form of public key, used in signature hash
+    edwards: [u8; WAVES_KEY_LENGTH],
+    // montgomery form of public key, waves account public key
+    public: [u8; WAVES_KEY_LENGTH],
+}
+
+impl WavesKey {
+    pub fn from_bytes(secret: &[u8]) -> Result<Self, Error> {
+        if secret.len() != WAVES_KEY_LENGTH {
+            return Err(Error::Signer(format!(
+                "invalid waves key length: {}",
+                secret.len()
+            )));
+        }
+        let mut key = [0u8; WAVES_KEY_LENGTH];
+        key.copy_from_slice(secret);
+        key[0] &= 248;
+        key[31] &= 127;
+        key[31] |= 64;
+
+        let scalar = Scalar::from_bits(key);
+        let point = &scalar * &ED25519_BASEPOINT_TABLE;
+        Ok(WavesKey {
+            secret: key,
+            scalar,
+            edwards: point.compress().to_bytes(),
+            public: point.to_montgomery().to_bytes(),
+        })
+    }
+
+    pub fn public_key(&self) -> [u8; WAVES_KEY_LENGTH] {
+        self.public
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; WAVES_SIGNATURE_LENGTH] {
+        let mut random = [0u8; 64];
+        rand::thread_rng().fill(&mut random[..]);
+        let r = Scalar::from_hash(
+            Sha512::new()
+                .chain([0xFE])
+                .chain([0xFF; 31])
+                .chain(self.secret)
+                .chain(message)
+                .chain(random),
+        );
+        let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain(big_r.as_bytes())
+                .chain(self.edwards)
+                .chain(message),
+        );
+        let s = r + k * self.scalar;
+
+        let mut signature = [0u8; WAVES_SIGNATURE_LENGTH];
+        signature[..32].copy_from_slice(big_r.as_bytes());
+        signature[32..].copy_from_slice(s.as_bytes());
+        signature[63] |= self.edwards[31] & SIGN_BIT;
+        signature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WavesKey, SIGN_BIT, WAVES_KEY_LENGTH, WAVES_SIGNATURE_LENGTH};
+    use curve25519_dalek::montgomery::MontgomeryPoint;
+    use ed25519_dalek::{PublicKey, Signature, Verifier};
+    use std::convert::TryFrom;
+
+    // ride `sigVerify` equivalent: rfc 8032 ed25519 verification with edwards
+    // public key recovered from account key and signature sign bit
+    fn verify(public_key: &[u8; WAVES_KEY_LENGTH], message: &[u8], signature: &[u8]) -> bool {
+        if signature.len() != WAVES_SIGNATURE_LENGTH {
+            return false;
+        }
+        let edwards = match MontgomeryPoint(*public_key).to_edwards((signature[63] & SIGN_BIT) >> 7)
+        {
+            Some(edwards) => edwards,
+            None => return false,
+        };
+        let mut signature = signature.to_vec();
+        signature[63] &= !SIGN_BIT;
+        match (
+            PublicKey::from_bytes(edwards.compress().as_bytes()),
+            Signature::try_from(signature.as_slice()),
+        ) {
+            (Ok(public_key), Ok(signature)) => public_key.verify(message, &signature).is_ok(),
+            _ => false,
+        }
+    }
+
+    // key pair of waves documentation "cryptographic practical details"
+    #[test]
+    fn reference_key() {
+        let secret = bs58::decode("3kMEhU5z3v8bmer1ERFUUhW58Dtuhyo9hE5vrhjqAWYT")
+            .into_vec()
+            .unwrap();
+        let key = WavesKey::from_bytes(&secret).unwrap();
+        assert_eq!(
+            bs58::encode(key.public_key()).into_string(),
+            "HBqhfdFASRQ5eBBpu2y6c6KKi1az6bMx8v1JxX4iW1Q8"
+        );
+        for _ in 0..16 {
+            let signature = key.sign(b"prices");
+            assert!(verify(&key.public_key(), b"prices", &signature));
+            assert!(!verify(&key.public_key(), b"other", &signature));
+        }
+    }
+}